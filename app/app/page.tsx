@@ -3,33 +3,80 @@
 
 import React, { useState } from "react";
 
+type SpeechJob = {
+  id: number;
+  status: "queued" | "running" | "done" | "failed";
+  chunks_total: number;
+  chunks_done: number;
+  error: string | null;
+  duration_ms: number | null;
+  audio_url: string | null;
+};
+
+const POLL_INTERVAL_MS = 1000;
+const FORMATS = ["mp3", "opus", "aac", "flac", "wav"] as const;
+
 export default function SpeechPage() {
   const [text, setText] = useState("");
+  const [format, setFormat] = useState<(typeof FORMATS)[number]>("mp3");
+  const [loading, setLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [job, setJob] = useState<SpeechJob | null>(null);
+
+  async function pollJob(id: number) {
+    for (;;) {
+      await new Promise((resolve) => setTimeout(resolve, POLL_INTERVAL_MS));
+      const res = await fetch(`/api/speech/${id}`);
+      if (!res.ok) {
+        throw new Error(await res.text());
+      }
+      const next: SpeechJob = await res.json();
+      setJob(next);
+      if (next.status === "done" || next.status === "failed") {
+        return next;
+      }
+    }
+  }
 
-  function handleSubmit(e: any) {
+  async function handleSubmit(e: any) {
     e.preventDefault();
+    setLoading(true);
+    setError(null);
+    setJob(null);
+
+    try {
+      const res = await fetch("/api/speech", {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ input: text, response_format: format }),
+      });
+
+      if (!res.ok) {
+        setError(await res.text());
+        return;
+      }
 
-    // Fire-and-forget: just call fetch with no awaits or .then handlers
-    fetch("/api/speech", {
-      method: "POST",
-      headers: { "Content-Type": "application/json" },
-      body: JSON.stringify({ input: text }),
-    });
+      const queued: SpeechJob = await res.json();
+      setJob(queued);
+      setText("");
 
-    // (Optional) You can clear the text or otherwise give user feedback immediately:
-    setText("");
+      const finished = await pollJob(queued.id);
+      if (finished.status === "failed") {
+        setError(finished.error ?? "Speech generation failed.");
+      }
+    } catch (err: any) {
+      setError(String(err));
+    } finally {
+      setLoading(false);
+    }
   }
 
   return (
     <div className="p-4 max-w-xl mx-auto">
-      <h1 className="text-xl font-bold mb-4">
-        Text-to-Speech Demo (Fire & Forget)
-      </h1>
+      <h1 className="text-xl font-bold mb-4">Text-to-Speech Demo</h1>
       <form onSubmit={handleSubmit} className="space-y-4">
         <div>
-          <label className="block mb-1 font-medium">
-            Text to Speak (no response):
-          </label>
+          <label className="block mb-1 font-medium">Text to Speak:</label>
           <textarea
             rows={5}
             value={text}
@@ -37,13 +84,52 @@ export default function SpeechPage() {
             className="border w-full p-2 text-black"
           />
         </div>
+        <div>
+          <label className="block mb-1 font-medium">Format:</label>
+          <select
+            value={format}
+            onChange={(e) => setFormat(e.target.value as typeof format)}
+            className="border p-2 text-black"
+          >
+            {FORMATS.map((f) => (
+              <option key={f} value={f}>
+                {f.toUpperCase()}
+              </option>
+            ))}
+          </select>
+        </div>
         <button
           type="submit"
-          className="px-4 py-2 bg-blue-600 text-white rounded"
+          disabled={loading}
+          className="px-4 py-2 bg-blue-600 text-white rounded disabled:opacity-50"
         >
-          Send Request
+          {loading ? "Generating..." : "Send Request"}
         </button>
       </form>
+
+      {error && <p className="mt-4 text-red-500">{error}</p>}
+
+      {job && job.status !== "done" && job.status !== "failed" && (
+        <p className="mt-4">
+          {job.status === "queued"
+            ? "Queued..."
+            : `Synthesizing chunk ${job.chunks_done} of ${job.chunks_total}...`}
+        </p>
+      )}
+
+      {job?.audio_url && (
+        <div className="mt-6 space-y-2">
+          <audio controls src={job.audio_url} className="w-full" />
+          <a href={job.audio_url} download className="underline">
+            Download clip #{job.id}
+          </a>
+          {job.duration_ms !== null && (
+            <span className="ml-2 text-sm text-gray-500">
+              ({(job.duration_ms / 1000).toFixed(1)}s)
+            </span>
+          )}
+        </div>
+      )}
     </div>
   );
 }
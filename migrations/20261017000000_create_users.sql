@@ -0,0 +1,7 @@
+-- Accounts used by the web app (see src/endpoints/auth.rs)
+CREATE TABLE IF NOT EXISTS users (
+    id SERIAL PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    password TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Synthesized audio produced by POST /api/speech, owned by the requesting user
+CREATE TABLE IF NOT EXISTS speech_clips (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    input TEXT NOT NULL,
+    voice TEXT NOT NULL,
+    audio BYTEA NOT NULL,
+    created_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS speech_clips_user_id_idx ON speech_clips (user_id);
@@ -0,0 +1,10 @@
+-- Content-addressed cache of synthesized speech (see src/services/tts_cache.rs)
+CREATE TABLE IF NOT EXISTS tts_cache (
+    key TEXT PRIMARY KEY,
+    audio BYTEA NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS tts_cache_last_used_at_idx ON tts_cache (last_used_at);
@@ -0,0 +1,17 @@
+-- Progress of asynchronous speech generation (see src/jobs/speech.rs)
+CREATE TABLE IF NOT EXISTS speech_jobs (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    input TEXT NOT NULL,
+    voice TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'done', 'failed')),
+    chunks_total INT NOT NULL DEFAULT 0,
+    chunks_done INT NOT NULL DEFAULT 0,
+    error TEXT,
+    clip_id INT REFERENCES speech_clips(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS speech_jobs_user_id_idx ON speech_jobs (user_id);
@@ -0,0 +1,8 @@
+-- Output format, model and speed of speech jobs (NULL model = provider default)
+ALTER TABLE speech_jobs
+    ADD COLUMN IF NOT EXISTS model TEXT,
+    ADD COLUMN IF NOT EXISTS response_format TEXT NOT NULL DEFAULT 'mp3',
+    ADD COLUMN IF NOT EXISTS speed REAL NOT NULL DEFAULT 1.0;
+
+ALTER TABLE speech_clips
+    ADD COLUMN IF NOT EXISTS response_format TEXT NOT NULL DEFAULT 'mp3';
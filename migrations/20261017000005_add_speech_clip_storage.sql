@@ -0,0 +1,7 @@
+-- Audio moves out of the database into AudioStorage; rows written before
+-- this keep their bytes in `audio` (NULL storage_key)
+ALTER TABLE speech_clips
+    ADD COLUMN IF NOT EXISTS storage_key TEXT,
+    ALTER COLUMN audio DROP NOT NULL;
+
+CREATE INDEX IF NOT EXISTS speech_clips_created_at_idx ON speech_clips (created_at);
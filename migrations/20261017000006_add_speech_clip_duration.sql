@@ -0,0 +1,3 @@
+-- Playback length of a clip, measured from its frames (NULL when unknown)
+ALTER TABLE speech_clips
+    ADD COLUMN IF NOT EXISTS duration_ms INT;
@@ -0,0 +1,19 @@
+-- When a company's email or industry was last filled in (PATCH /api/companies/:id)
+ALTER TABLE companies
+    ADD COLUMN IF NOT EXISTS enriched_at TIMESTAMP;
+
+-- Secret part of each user's private podcast feed URL
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS feed_token TEXT UNIQUE;
+
+-- One spoken briefing per user and day; its audio comes from a speech job
+CREATE TABLE IF NOT EXISTS briefings (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    briefing_date DATE NOT NULL,
+    title TEXT NOT NULL,
+    script TEXT NOT NULL,
+    speech_job_id INT REFERENCES speech_jobs(id) ON DELETE SET NULL,
+    created_at TIMESTAMP DEFAULT NOW(),
+    UNIQUE (user_id, briefing_date)
+);
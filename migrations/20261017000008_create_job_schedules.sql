@@ -0,0 +1,20 @@
+-- Named cron schedules that push jobs onto their kind's queue (see src/jobs/schedules.rs)
+CREATE TABLE IF NOT EXISTS job_schedules (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    kind TEXT NOT NULL,
+    cron TEXT NOT NULL,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Claimed by the scheduler with a compare-and-set before each run
+    next_run_at TIMESTAMPTZ,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- The schedules that used to be hard-coded in MyService::bind
+INSERT INTO job_schedules (name, kind, cron)
+VALUES
+    ('morning-cereal', 'reminder', '0 */2 * * * *'),
+    ('daily-briefing', 'briefing', '0 0 7 * * *')
+ON CONFLICT (name) DO NOTHING;
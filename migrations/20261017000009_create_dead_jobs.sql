@@ -0,0 +1,17 @@
+-- Who may use the /api/admin endpoints
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Jobs that failed every attempt (see src/jobs/dead_letter.rs)
+CREATE TABLE IF NOT EXISTS dead_jobs (
+    id SERIAL PRIMARY KEY,
+    worker TEXT NOT NULL,
+    namespace TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    -- The final error first, followed by each of its sources
+    error_chain TEXT[] NOT NULL,
+    attempts INT NOT NULL,
+    failed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS dead_jobs_failed_at_idx ON dead_jobs (failed_at);
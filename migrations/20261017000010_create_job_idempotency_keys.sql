@@ -0,0 +1,8 @@
+-- Side effects of scheduled jobs that already happened, one row per job kind
+-- and scheduled time (see src/jobs/idempotency.rs)
+CREATE TABLE IF NOT EXISTS job_idempotency_keys (
+    kind TEXT NOT NULL,
+    scheduled_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (kind, scheduled_at)
+);
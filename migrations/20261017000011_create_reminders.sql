@@ -0,0 +1,35 @@
+-- Where a user's reminder emails go; without one they only get notifications
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS email TEXT;
+
+-- "Follow up with this contact at this time" (POST /api/reminders), sent by
+-- the follow-up job in src/jobs/follow_up.rs
+CREATE TABLE IF NOT EXISTS reminders (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    contact_id INT NOT NULL REFERENCES contacts(id) ON DELETE CASCADE,
+    note TEXT,
+    remind_at TIMESTAMPTZ NOT NULL,
+    -- Absolute URL of the contact, for the email
+    contact_url TEXT NOT NULL,
+    sent_at TIMESTAMPTZ,
+    cancelled_at TIMESTAMPTZ,
+    created_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS reminders_user_id_remind_at_idx ON reminders (user_id, remind_at);
+
+-- In-app notifications (GET /api/notifications)
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    link TEXT,
+    contact_id INT REFERENCES contacts(id) ON DELETE SET NULL,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_id_created_at_idx
+    ON notifications (user_id, created_at DESC);
@@ -0,0 +1,4 @@
+-- Reminder emails no longer link to the contact: the only URL for one was
+-- the cookie-authenticated JSON API, which a mail client can't open
+ALTER TABLE reminders
+    DROP COLUMN IF EXISTS contact_url;
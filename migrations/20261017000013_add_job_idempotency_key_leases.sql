@@ -0,0 +1,9 @@
+-- Keys are claimed ('pending') before their side effect runs and marked
+-- 'done' after it, instead of being held in a transaction throughout;
+-- a pending claim older than its lease was abandoned by a crashed worker
+ALTER TABLE job_idempotency_keys
+    ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'done'
+        CHECK (status IN ('pending', 'done')),
+    ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ALTER COLUMN completed_at DROP NOT NULL,
+    ALTER COLUMN completed_at DROP DEFAULT;
@@ -0,0 +1,4 @@
+-- Size of a clip's stored audio, for podcast enclosures (NULL for clips
+-- made before this)
+ALTER TABLE speech_clips
+    ADD COLUMN IF NOT EXISTS size_bytes BIGINT;
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::services::tts_service::AudioFormat;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct SignedQuery {
+    expires: i64,
+    signature: String,
+}
+
+/// Serves audio from local storage to whoever holds a valid signed URL; no
+/// session needed, so `<audio>` tags and email links work as is.
+pub async fn download_audio(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Query(query): Query<SignedQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if !state
+        .audio_storage
+        .verify_url(&key, query.expires, &query.signature)
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Invalid or expired link.".to_string(),
+        ));
+    }
+
+    let audio = state
+        .audio_storage
+        .get(&key)
+        .await
+        .map_err(|e| {
+            error!("Unable to read {key}: {e:#}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Unable to read audio.".to_string(),
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "Not found.".to_string()))?;
+
+    let content_type = key
+        .rsplit_once('.')
+        .and_then(|(_, extension)| AudioFormat::from_extension(extension))
+        .map_or("application/octet-stream", AudioFormat::content_type);
+
+    Ok(([(header::CONTENT_TYPE, content_type)], audio))
+}
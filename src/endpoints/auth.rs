@@ -16,12 +16,16 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
 
+use crate::endpoints::db_error;
 use crate::state::AppState;
 
 #[derive(Deserialize)]
 pub struct AuthRequest {
     username: String,
     password: String,
+    /// Where reminder emails go; only read by [`register`].
+    #[serde(default)]
+    email: Option<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -33,9 +37,15 @@ pub async fn register(
 ) -> Result<impl IntoResponse, impl IntoResponse> {
     let hash = hash_password(&json.password).unwrap();
 
-    if let Err(e) = sqlx::query("INSERT INTO users (username, password) VALUES ($1, $2)")
+    let email = json
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|e| !e.is_empty());
+    if let Err(e) = sqlx::query("INSERT INTO users (username, password, email) VALUES ($1, $2, $3)")
         .bind(&json.username)
         .bind(hash)
+        .bind(email)
         .execute(&state.db)
         .await
     {
@@ -138,6 +148,30 @@ impl FromRequestParts<AppState> for Claims {
     }
 }
 
+/// A logged-in user with `users.is_admin` set; guards the `/api/admin` routes.
+pub struct Admin(pub Claims);
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = (StatusCode, String);
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        let is_admin: Option<(bool,)> = sqlx::query_as("SELECT is_admin FROM users WHERE id = $1")
+            .bind(claims.user_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(db_error)?;
+        match is_admin {
+            Some((true,)) => Ok(Admin(claims)),
+            _ => Err((StatusCode::FORBIDDEN, "Admins only.".to_string())),
+        }
+    }
+}
+
 impl Claims {
     pub fn new(user_id: i32, username: &str) -> Self {
         let exp = 2000000000;
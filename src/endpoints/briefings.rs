@@ -0,0 +1,234 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+use std::{fmt::Write, time::Duration};
+use tracing::warn;
+
+use crate::endpoints::{absolute_url, auth::Claims, db_error};
+use crate::services::audio_storage::DEFAULT_URL_TTL;
+use crate::services::tts_service::AudioFormat;
+use crate::state::AppState;
+
+/// Podcast apps fetch episodes long after the feed, so their links last longer.
+const FEED_URL_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Episodes listed in the feed, newest first.
+const FEED_ITEMS: i64 = 30;
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Briefing {
+    id: i32,
+    briefing_date: NaiveDate,
+    title: String,
+    script: String,
+    /// Status of the speech job making the audio, if there is one.
+    status: Option<String>,
+    duration_ms: Option<i32>,
+    created_at: Option<NaiveDateTime>,
+    #[serde(skip)]
+    storage_key: Option<String>,
+    #[serde(skip)]
+    response_format: Option<String>,
+    #[serde(skip)]
+    size_bytes: Option<i64>,
+    #[sqlx(skip)]
+    audio_url: Option<String>,
+}
+
+const BRIEFING_QUERY: &str = r#"
+    SELECT b.id, b.briefing_date, b.title, b.script, j.status, c.duration_ms, b.created_at,
+        c.storage_key, c.response_format, c.size_bytes
+    FROM briefings b
+    LEFT JOIN speech_jobs j ON j.id = b.speech_job_id
+    LEFT JOIN speech_clips c ON c.id = j.clip_id
+"#;
+
+/// The current user's briefings, newest first, with signed audio URLs for
+/// the finished ones.
+pub async fn list_briefings(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut briefings: Vec<Briefing> = sqlx::query_as(&format!(
+        "{BRIEFING_QUERY} WHERE b.user_id = $1 ORDER BY b.briefing_date DESC LIMIT $2"
+    ))
+    .bind(claims.user_id())
+    .bind(FEED_ITEMS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    for briefing in &mut briefings {
+        briefing.audio_url = briefing.storage_key.as_deref().and_then(|key| {
+            state
+                .audio_storage
+                .signed_url(key, DEFAULT_URL_TTL)
+                .inspect_err(|e| warn!("Unable to sign a URL for {key}: {e:#}"))
+                .ok()
+        });
+    }
+
+    Ok(Json(briefings))
+}
+
+#[derive(Serialize)]
+pub struct FeedUrl {
+    url: String,
+}
+
+/// SQL for a fresh feed token: 122 random bits as 32 hex digits.
+const NEW_TOKEN: &str = "replace(gen_random_uuid()::text, '-', '')";
+
+/// The current user's private podcast feed URL. Anyone with the URL can
+/// listen, so treat it like a password; [`rotate_feed`] revokes it.
+pub async fn get_feed(
+    claims: Claims,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    set_feed_token(
+        &state,
+        &claims,
+        &headers,
+        &format!("COALESCE(feed_token, {NEW_TOKEN})"),
+    )
+    .await
+}
+
+/// Replaces the feed token, so previously shared feed URLs stop working.
+pub async fn rotate_feed(
+    claims: Claims,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    set_feed_token(&state, &claims, &headers, NEW_TOKEN).await
+}
+
+async fn set_feed_token(
+    state: &AppState,
+    claims: &Claims,
+    headers: &HeaderMap,
+    token_sql: &str,
+) -> Result<Json<FeedUrl>, (StatusCode, String)> {
+    let (token,): (String,) = sqlx::query_as(&format!(
+        "UPDATE users SET feed_token = {token_sql} WHERE id = $1 RETURNING feed_token"
+    ))
+    .bind(claims.user_id())
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(FeedUrl {
+        url: absolute_url(headers, &format!("/api/podcast/{token}")),
+    }))
+}
+
+/// RSS 2.0 podcast feed of a user's finished briefings. The token in the
+/// path is the only credential.
+pub async fn podcast_feed(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (user_id, username): (i32, String) =
+        sqlx::query_as("SELECT id, username FROM users WHERE feed_token = $1")
+            .bind(&token)
+            .fetch_one(&state.db)
+            .await
+            .map_err(db_error)?;
+    let episodes: Vec<Briefing> = sqlx::query_as(&format!(
+        r#"{BRIEFING_QUERY}
+        WHERE b.user_id = $1 AND c.storage_key IS NOT NULL
+        ORDER BY b.briefing_date DESC
+        LIMIT $2
+        "#
+    ))
+    .bind(user_id)
+    .bind(FEED_ITEMS)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    let mut rss = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+<channel>
+<title>{}</title>
+<link>{}</link>
+<description>Your CRM activity since yesterday, read aloud every morning.</description>
+<language>en</language>
+<itunes:block>Yes</itunes:block>
+"#,
+        escape_xml(&format!("Daily briefing for {username}")),
+        escape_xml(&absolute_url(&headers, "/")),
+    );
+    for episode in episodes {
+        let Some(key) = episode.storage_key.as_deref() else {
+            continue;
+        };
+        let url = match state.audio_storage.signed_url(key, FEED_URL_TTL) {
+            Ok(url) => absolute_url(&headers, &url),
+            Err(e) => {
+                warn!("Leaving briefing {} out of the feed: {e:#}", episode.id);
+                continue;
+            }
+        };
+        let format: AudioFormat = episode
+            .response_format
+            .as_deref()
+            .and_then(|f| f.parse().ok())
+            .unwrap_or_default();
+        let published = episode
+            .created_at
+            .unwrap_or_else(|| episode.briefing_date.and_time(NaiveTime::MIN))
+            .and_utc();
+
+        let _ = write!(
+            rss,
+            r#"<item>
+<title>{}</title>
+<guid isPermaLink="false">briefing-{}</guid>
+<pubDate>{}</pubDate>
+<description>{}</description>
+<enclosure url="{}" length="{}" type="{}"/>
+"#,
+            escape_xml(&episode.title),
+            episode.id,
+            published.to_rfc2822(),
+            escape_xml(&episode.script),
+            escape_xml(&url),
+            // 0 when unknown, as RSS readers expect.
+            episode.size_bytes.unwrap_or(0),
+            format.content_type(),
+        );
+        if let Some(ms) = episode.duration_ms {
+            let _ = writeln!(rss, "<itunes:duration>{}</itunes:duration>", ms / 1000);
+        }
+        rss.push_str("</item>\n");
+    }
+    rss.push_str("</channel>\n</rss>\n");
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        rss,
+    ))
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
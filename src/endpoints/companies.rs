@@ -0,0 +1,315 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, Postgres, QueryBuilder};
+
+use crate::endpoints::{
+    auth::Claims,
+    contacts::{fetch_contact_page, ContactQuery},
+    db_error, page_bounds, present,
+};
+use crate::state::AppState;
+
+/// Companies joined with the number of contacts pointing at them via `contacts.company_id`.
+const COMPANY_SELECT: &str = r#"
+    SELECT
+        c.id, c.name, c.website, c.email, c.industry, c.created_at,
+        COUNT(ct.id) AS contact_count
+    FROM companies c
+    LEFT JOIN contacts ct ON ct.company_id = c.id
+"#;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Company {
+    id: i32,
+    name: String,
+    website: String,
+    email: Option<String>,
+    industry: Option<String>,
+    created_at: Option<NaiveDateTime>,
+    contact_count: i64,
+}
+
+/// Query string accepted by `GET /api/companies`.
+#[derive(Debug, Deserialize)]
+pub struct CompanyQuery {
+    name: Option<String>,
+    industry: Option<String>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompanyPage {
+    companies: Vec<Company>,
+    page: i64,
+    per_page: i64,
+    total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewCompany {
+    name: String,
+    website: String,
+    email: Option<String>,
+    industry: Option<String>,
+}
+
+/// Partial update for `PATCH /api/companies/:id`; only the fields present are
+/// changed, and an explicit `null` clears a nullable one.
+#[derive(Debug, Deserialize)]
+pub struct UpdateCompany {
+    name: Option<String>,
+    website: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    email: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    industry: Option<Option<String>>,
+}
+
+/// Query string accepted by `DELETE /api/companies/:id`.
+///
+/// Without `reassign_to` the delete is refused while contacts still reference
+/// the company; with it, those contacts are moved to the given company first.
+#[derive(Debug, Deserialize)]
+pub struct DeleteCompanyQuery {
+    reassign_to: Option<i32>,
+}
+
+async fn fetch_company<'e>(db: impl PgExecutor<'e>, id: i32) -> Result<Company, sqlx::Error> {
+    sqlx::query_as(&format!("{COMPANY_SELECT} WHERE c.id = $1 GROUP BY c.id"))
+        .bind(id)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn list_companies(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<CompanyQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+
+    let push_filters = |qb: &mut QueryBuilder<'_, Postgres>| {
+        qb.push(" WHERE TRUE");
+        if let Some(name) = &query.name {
+            qb.push(" AND c.name ILIKE ")
+                .push_bind(format!("%{}%", name.trim()));
+        }
+        if let Some(industry) = &query.industry {
+            qb.push(" AND c.industry ILIKE ")
+                .push_bind(format!("%{}%", industry.trim()));
+        }
+    };
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM companies c");
+    push_filters(&mut count);
+    let total: i64 = count
+        .build_query_scalar()
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    let mut select = QueryBuilder::new(COMPANY_SELECT);
+    push_filters(&mut select);
+    select
+        .push(" GROUP BY c.id ORDER BY c.name LIMIT ")
+        .push_bind(per_page)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * per_page);
+
+    let companies = select
+        .build_query_as::<Company>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(CompanyPage {
+        companies,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+pub async fn get_company(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let company = fetch_company(&state.db, id).await.map_err(db_error)?;
+
+    Ok(Json(company))
+}
+
+pub async fn list_company_contacts(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(mut query): Query<ContactQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // 404 for unknown companies rather than an empty page
+    sqlx::query("SELECT 1 FROM companies WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    query.company_id = Some(id);
+    let page = fetch_contact_page(&state.db, &query)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(page))
+}
+
+pub async fn create_company(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<NewCompany>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (id,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO companies
+            (name, website, email, industry)
+        VALUES
+            ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(&json.name)
+    .bind(&json.website)
+    .bind(&json.email)
+    .bind(&json.industry)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    let company = fetch_company(&state.db, id).await.map_err(db_error)?;
+
+    Ok((StatusCode::CREATED, Json(company)))
+}
+
+pub async fn update_company(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(json): Json<UpdateCompany>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut tx = state.db.begin().await.map_err(db_error)?;
+
+    let mut qb = QueryBuilder::<Postgres>::new("UPDATE companies SET id = id");
+    if let Some(name) = &json.name {
+        qb.push(", name = ").push_bind(name);
+    }
+    if let Some(website) = &json.website {
+        qb.push(", website = ").push_bind(website);
+    }
+    if let Some(email) = &json.email {
+        qb.push(", email = ").push_bind(email);
+    }
+    if let Some(industry) = &json.industry {
+        qb.push(", industry = ").push_bind(industry);
+    }
+    // Daily briefings report companies whose details were filled in.
+    if matches!(json.email, Some(Some(_))) || matches!(json.industry, Some(Some(_))) {
+        qb.push(", enriched_at = NOW()");
+    }
+    qb.push(" WHERE id = ").push_bind(id);
+
+    let result = qb.build().execute(&mut *tx).await.map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err(db_error(sqlx::Error::RowNotFound));
+    }
+
+    // Keep the denormalized `contacts.company` name in step with the company row.
+    if let Some(name) = &json.name {
+        sqlx::query("UPDATE contacts SET company = $1 WHERE company_id = $2")
+            .bind(name)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    }
+
+    let company = fetch_company(&mut *tx, id).await.map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(Json(company))
+}
+
+pub async fn delete_company(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(query): Query<DeleteCompanyQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut tx = state.db.begin().await.map_err(db_error)?;
+
+    let company = fetch_company(&mut *tx, id).await.map_err(db_error)?;
+
+    match query.reassign_to {
+        Some(target) if target == id => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Cannot reassign contacts to the company being deleted.".to_string(),
+            ));
+        }
+        Some(target) => {
+            let (name,): (String,) = sqlx::query_as("SELECT name FROM companies WHERE id = $1")
+                .bind(target)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(db_error)?
+                .ok_or((
+                    StatusCode::BAD_REQUEST,
+                    format!("Company {target} to reassign contacts to does not exist."),
+                ))?;
+
+            sqlx::query("UPDATE contacts SET company_id = $1, company = $2 WHERE company_id = $3")
+                .bind(target)
+                .bind(name)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+        None if company.contact_count > 0 => {
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Company {id} still has {} contacts; pass `reassign_to` to move them first.",
+                    company.contact_count
+                ),
+            ));
+        }
+        None => {}
+    }
+
+    // Checked again as part of the delete, so a contact added since the
+    // count above can't be left pointing at a deleted company.
+    let result = sqlx::query(
+        r#"
+        DELETE FROM companies
+        WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM contacts WHERE company_id = $1)
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Company {id} gained contacts while being deleted; try again."),
+        ));
+    }
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,307 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::endpoints::{auth::Claims, db_error, page_bounds, present};
+use crate::state::AppState;
+
+const CONTACT_COLUMNS: &str =
+    "id, first_name, last_name, url, email_address, company, position, company_id, created_at";
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Contact {
+    id: i32,
+    first_name: String,
+    last_name: String,
+    url: String,
+    email_address: Option<String>,
+    company: String,
+    position: String,
+    company_id: Option<i32>,
+    created_at: Option<NaiveDateTime>,
+}
+
+/// Columns a contact listing can be sorted on.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+    #[default]
+    Id,
+    FirstName,
+    LastName,
+    Url,
+    EmailAddress,
+    Company,
+    Position,
+    CompanyId,
+    CreatedAt,
+}
+
+impl SortColumn {
+    fn as_sql(self) -> &'static str {
+        match self {
+            SortColumn::Id => "id",
+            SortColumn::FirstName => "first_name",
+            SortColumn::LastName => "last_name",
+            SortColumn::Url => "url",
+            SortColumn::EmailAddress => "email_address",
+            SortColumn::Company => "company",
+            SortColumn::Position => "position",
+            SortColumn::CompanyId => "company_id",
+            SortColumn::CreatedAt => "created_at",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Query string accepted by `GET /api/contacts`.
+///
+/// `company` and `position` are case-insensitive substring matches, the
+/// `created_*` bounds are inclusive, and `page` starts at 1.
+#[derive(Debug, Deserialize)]
+pub struct ContactQuery {
+    company: Option<String>,
+    pub(crate) company_id: Option<i32>,
+    position: Option<String>,
+    created_after: Option<NaiveDateTime>,
+    created_before: Option<NaiveDateTime>,
+    #[serde(default)]
+    sort: SortColumn,
+    #[serde(default)]
+    order: SortOrder,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+impl ContactQuery {
+    fn push_filters(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        qb.push(" WHERE TRUE");
+
+        if let Some(company) = &self.company {
+            qb.push(" AND company ILIKE ")
+                .push_bind(format!("%{}%", company.trim()));
+        }
+        if let Some(company_id) = self.company_id {
+            qb.push(" AND company_id = ").push_bind(company_id);
+        }
+        if let Some(position) = &self.position {
+            qb.push(" AND position ILIKE ")
+                .push_bind(format!("%{}%", position.trim()));
+        }
+        if let Some(after) = self.created_after {
+            qb.push(" AND created_at >= ").push_bind(after);
+        }
+        if let Some(before) = self.created_before {
+            qb.push(" AND created_at <= ").push_bind(before);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContactPage {
+    contacts: Vec<Contact>,
+    page: i64,
+    per_page: i64,
+    total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewContact {
+    first_name: String,
+    last_name: String,
+    url: String,
+    email_address: Option<String>,
+    company: String,
+    position: String,
+    company_id: Option<i32>,
+}
+
+/// Partial update for `PATCH /api/contacts/:id`; only the fields present are
+/// changed, and an explicit `null` clears a nullable one.
+#[derive(Debug, Deserialize)]
+pub struct UpdateContact {
+    first_name: Option<String>,
+    last_name: Option<String>,
+    url: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    email_address: Option<Option<String>>,
+    company: Option<String>,
+    position: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    company_id: Option<Option<i32>>,
+}
+
+/// Runs a filtered, sorted and paged contact listing.
+pub(crate) async fn fetch_contact_page(
+    db: &PgPool,
+    query: &ContactQuery,
+) -> Result<ContactPage, sqlx::Error> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM contacts");
+    query.push_filters(&mut count);
+    let total: i64 = count.build_query_scalar().fetch_one(db).await?;
+
+    let mut select = QueryBuilder::new(format!("SELECT {CONTACT_COLUMNS} FROM contacts"));
+    query.push_filters(&mut select);
+    // Sort column and direction come from closed enums, so they are safe to inline.
+    select
+        .push(format!(
+            " ORDER BY {} {} NULLS LAST, id {}",
+            query.sort.as_sql(),
+            query.order.as_sql(),
+            query.order.as_sql()
+        ))
+        .push(" LIMIT ")
+        .push_bind(per_page)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * per_page);
+
+    let contacts = select.build_query_as::<Contact>().fetch_all(db).await?;
+
+    Ok(ContactPage {
+        contacts,
+        page,
+        per_page,
+        total,
+    })
+}
+
+pub async fn list_contacts(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<ContactQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let page = fetch_contact_page(&state.db, &query)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(page))
+}
+
+pub async fn get_contact(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let contact: Contact = sqlx::query_as(&format!(
+        "SELECT {CONTACT_COLUMNS} FROM contacts WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(contact))
+}
+
+pub async fn create_contact(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<NewContact>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let contact: Contact = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO contacts
+            (first_name, last_name, url, email_address, company, position, company_id)
+        VALUES
+            ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {CONTACT_COLUMNS}
+        "#
+    ))
+    .bind(&json.first_name)
+    .bind(&json.last_name)
+    .bind(&json.url)
+    .bind(&json.email_address)
+    .bind(&json.company)
+    .bind(&json.position)
+    .bind(json.company_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok((StatusCode::CREATED, Json(contact)))
+}
+
+pub async fn update_contact(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(json): Json<UpdateContact>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut qb = QueryBuilder::<Postgres>::new("UPDATE contacts SET id = id");
+
+    if let Some(first_name) = json.first_name {
+        qb.push(", first_name = ").push_bind(first_name);
+    }
+    if let Some(last_name) = json.last_name {
+        qb.push(", last_name = ").push_bind(last_name);
+    }
+    if let Some(url) = json.url {
+        qb.push(", url = ").push_bind(url);
+    }
+    if let Some(email_address) = json.email_address {
+        qb.push(", email_address = ").push_bind(email_address);
+    }
+    if let Some(company) = json.company {
+        qb.push(", company = ").push_bind(company);
+    }
+    if let Some(position) = json.position {
+        qb.push(", position = ").push_bind(position);
+    }
+    if let Some(company_id) = json.company_id {
+        qb.push(", company_id = ").push_bind(company_id);
+    }
+
+    qb.push(" WHERE id = ")
+        .push_bind(id)
+        .push(format!(" RETURNING {CONTACT_COLUMNS}"));
+
+    let contact = qb
+        .build_query_as::<Contact>()
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(contact))
+}
+
+pub async fn delete_contact(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let result = sqlx::query("DELETE FROM contacts WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err(db_error(sqlx::Error::RowNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
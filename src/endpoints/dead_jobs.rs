@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::endpoints::{auth::Admin, db_error, page_bounds};
+use crate::jobs::registry::{self, RequeueError};
+use crate::state::AppState;
+
+/// A row of `GET /api/admin/dead-jobs`; the payload and the rest of the error
+/// chain are in [`DeadJob`].
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DeadJobSummary {
+    id: i32,
+    worker: String,
+    /// The final error, without its sources.
+    error: Option<String>,
+    attempts: i32,
+    failed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DeadJob {
+    id: i32,
+    worker: String,
+    namespace: String,
+    payload: Value,
+    error_chain: Vec<String>,
+    attempts: i32,
+    failed_at: NaiveDateTime,
+}
+
+/// Query string accepted by `GET /api/admin/dead-jobs`.
+#[derive(Debug, Deserialize)]
+pub struct DeadJobQuery {
+    worker: Option<String>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadJobPage {
+    dead_jobs: Vec<DeadJobSummary>,
+    page: i64,
+    per_page: i64,
+    total: i64,
+}
+
+/// Dead jobs, most recent failure first.
+pub async fn list_dead_jobs(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Query(query): Query<DeadJobQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+
+    let push_filters = |qb: &mut QueryBuilder<'_, Postgres>| {
+        qb.push(" WHERE TRUE");
+        if let Some(worker) = &query.worker {
+            qb.push(" AND worker = ")
+                .push_bind(worker.trim().to_string());
+        }
+    };
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM dead_jobs");
+    push_filters(&mut count);
+    let total: i64 = count
+        .build_query_scalar()
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    let mut select = QueryBuilder::new(
+        "SELECT id, worker, error_chain[1] AS error, attempts, failed_at FROM dead_jobs",
+    );
+    push_filters(&mut select);
+    select
+        .push(" ORDER BY failed_at DESC, id DESC LIMIT ")
+        .push_bind(per_page)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * per_page);
+
+    let dead_jobs = select
+        .build_query_as::<DeadJobSummary>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(DeadJobPage {
+        dead_jobs,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+pub async fn get_dead_job(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let dead_job: DeadJob = sqlx::query_as(
+        r#"
+        SELECT id, worker, namespace, payload, error_chain, attempts, failed_at
+        FROM dead_jobs
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(dead_job))
+}
+
+/// Puts the job back on its queue with a fresh set of attempts and removes
+/// it from the dead-letter queue.
+pub async fn requeue_dead_job(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut tx = state.db.begin().await.map_err(db_error)?;
+
+    // Locked so a concurrent requeue can't push the job twice.
+    let (namespace, payload): (String, Value) =
+        sqlx::query_as("SELECT namespace, payload FROM dead_jobs WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+    registry::requeue(&state.db, &namespace, payload)
+        .await
+        .map_err(|e| match e {
+            RequeueError::Queue(e) => db_error(e),
+            e => (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+        })?;
+
+    sqlx::query("DELETE FROM dead_jobs WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn discard_dead_job(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let result = sqlx::query("DELETE FROM dead_jobs WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err(db_error(sqlx::Error::RowNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
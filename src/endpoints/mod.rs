@@ -1,5 +1,154 @@
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    routing::{delete, get, post},
+    Router,
+};
+use serde::{Deserialize, Deserializer};
+use tower_http::services::{ServeDir, ServeFile};
+
+use crate::state::AppState;
+
+pub mod audio;
 pub mod auth;
+pub mod briefings;
+pub mod companies;
+pub mod contacts;
+pub mod dead_jobs;
+pub mod notifications;
+pub mod reminders;
+pub mod schedules;
+pub mod speech;
+
+const DEFAULT_PER_PAGE: i64 = 50;
+const MAX_PER_PAGE: i64 = 200;
+
+/// Normalizes `page`/`per_page` query parameters: pages start at 1 and
+/// `per_page` is clamped to `1..=MAX_PER_PAGE`.
+pub(crate) fn page_bounds(page: Option<i64>, per_page: Option<i64>) -> (i64, i64) {
+    (
+        page.unwrap_or(1).max(1),
+        per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE),
+    )
+}
 
 pub async fn health_check() -> &'static str {
     "Hello, world!"
 }
+
+/// Maps a sqlx error onto the `(StatusCode, String)` rejection our handlers return.
+pub(crate) fn db_error(e: sqlx::Error) -> (StatusCode, String) {
+    match e {
+        sqlx::Error::RowNotFound => (StatusCode::NOT_FOUND, "Not found.".to_string()),
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            (StatusCode::CONFLICT, format!("Already exists: {e}"))
+        }
+        e => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {e}"),
+        ),
+    }
+}
+
+/// `Some` for a field that's in the body, even as `null`; with
+/// `#[serde(default)]`, a missing one stays `None`. Lets a PATCH tell
+/// "clear this field" from "leave it alone".
+pub(crate) fn present<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Makes a server-relative URL absolute using the request's host, for
+/// clients that fetch it from elsewhere (podcast apps, email).
+pub(crate) fn absolute_url(headers: &HeaderMap, url: &str) -> String {
+    if !url.starts_with('/') {
+        return url.to_string();
+    }
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let proto = header("x-forwarded-proto").unwrap_or("https");
+    let host = header(header::HOST.as_str()).unwrap_or("localhost");
+    format!("{proto}://{host}{url}")
+}
+
+/// Builds the app router: the JSON API lives under `/api`, everything else
+/// falls through to the exported Next.js frontend in `dist/`.
+pub fn router(state: AppState) -> Router {
+    let api = Router::new()
+        .route("/health", get(health_check))
+        .route("/auth/register", post(auth::register))
+        .route("/auth/login", post(auth::login))
+        .route(
+            "/contacts",
+            get(contacts::list_contacts).post(contacts::create_contact),
+        )
+        .route(
+            "/contacts/:id",
+            get(contacts::get_contact)
+                .patch(contacts::update_contact)
+                .delete(contacts::delete_contact),
+        )
+        .route(
+            "/companies",
+            get(companies::list_companies).post(companies::create_company),
+        )
+        .route(
+            "/companies/:id",
+            get(companies::get_company)
+                .patch(companies::update_company)
+                .delete(companies::delete_company),
+        )
+        .route(
+            "/companies/:id/contacts",
+            get(companies::list_company_contacts),
+        )
+        .route("/speech", post(speech::create_speech))
+        .route("/speech/stream", post(speech::stream_speech))
+        .route("/speech/cache", get(speech::cache_stats))
+        .route("/speech/:id", get(speech::get_speech))
+        .route("/speech/:id/audio", get(speech::get_speech_audio))
+        .route("/audio/*key", get(audio::download_audio))
+        .route("/briefings", get(briefings::list_briefings))
+        .route(
+            "/briefings/feed",
+            get(briefings::get_feed).post(briefings::rotate_feed),
+        )
+        .route("/podcast/:token", get(briefings::podcast_feed))
+        .route(
+            "/reminders",
+            get(reminders::list_reminders).post(reminders::create_reminder),
+        )
+        .route("/reminders/:id", delete(reminders::cancel_reminder))
+        .route("/notifications", get(notifications::list_notifications))
+        .route(
+            "/notifications/:id/read",
+            post(notifications::mark_notification_read),
+        )
+        .route(
+            "/schedules",
+            get(schedules::list_schedules).post(schedules::create_schedule),
+        )
+        .route(
+            "/schedules/:id",
+            get(schedules::get_schedule).delete(schedules::delete_schedule),
+        )
+        .route("/schedules/:id/pause", post(schedules::pause_schedule))
+        .route("/schedules/:id/resume", post(schedules::resume_schedule))
+        .route("/admin/dead-jobs", get(dead_jobs::list_dead_jobs))
+        .route(
+            "/admin/dead-jobs/:id",
+            get(dead_jobs::get_dead_job).delete(dead_jobs::discard_dead_job),
+        )
+        .route(
+            "/admin/dead-jobs/:id/requeue",
+            post(dead_jobs::requeue_dead_job),
+        );
+
+    Router::new()
+        .nest("/api", api)
+        .fallback_service(
+            ServeDir::new("dist").not_found_service(ServeFile::new("dist/index.html")),
+        )
+        .with_state(state)
+}
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::endpoints::{auth::Claims, db_error, page_bounds};
+use crate::state::AppState;
+
+const NOTIFICATION_COLUMNS: &str = "id, title, body, link, contact_id, read_at, created_at";
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Notification {
+    id: i32,
+    title: String,
+    body: String,
+    /// Server-relative URL of what the notification is about.
+    link: Option<String>,
+    contact_id: Option<i32>,
+    read_at: Option<DateTime<Utc>>,
+    created_at: NaiveDateTime,
+}
+
+/// Query string accepted by `GET /api/notifications`.
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    #[serde(default)]
+    unread: bool,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationPage {
+    notifications: Vec<Notification>,
+    page: i64,
+    per_page: i64,
+    total: i64,
+}
+
+/// The current user's notifications, newest first; `?unread=true` leaves
+/// out the ones already read.
+pub async fn list_notifications(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+
+    let (user_id, unread) = (*claims.user_id(), query.unread);
+    let push_filters = move |qb: &mut QueryBuilder<'_, Postgres>| {
+        qb.push(" WHERE user_id = ").push_bind(user_id);
+        if unread {
+            qb.push(" AND read_at IS NULL");
+        }
+    };
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM notifications");
+    push_filters(&mut count);
+    let total: i64 = count
+        .build_query_scalar()
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    let mut select = QueryBuilder::new(format!("SELECT {NOTIFICATION_COLUMNS} FROM notifications"));
+    push_filters(&mut select);
+    select
+        .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+        .push_bind(per_page)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * per_page);
+
+    let notifications = select
+        .build_query_as::<Notification>()
+        .fetch_all(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(NotificationPage {
+        notifications,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+pub async fn mark_notification_read(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let notification: Notification = sqlx::query_as(&format!(
+        r#"
+        UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING {NOTIFICATION_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(claims.user_id())
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(notification))
+}
@@ -0,0 +1,202 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::endpoints::{auth::Claims, db_error};
+use crate::jobs::follow_up;
+use crate::services::date_agent;
+use crate::state::AppState;
+use crate::utils::natural_date::{self, DateParseError};
+
+const REMINDER_COLUMNS: &str = "id, contact_id, note, remind_at, sent_at, created_at";
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Reminder {
+    id: i32,
+    contact_id: i32,
+    note: Option<String>,
+    remind_at: DateTime<Utc>,
+    sent_at: Option<DateTime<Utc>>,
+    created_at: Option<NaiveDateTime>,
+}
+
+/// Takes either `remind_at` or `when`.
+#[derive(Debug, Deserialize)]
+pub struct NewReminder {
+    contact_id: i32,
+    /// RFC 3339, such as `2026-10-20T09:00:00+02:00`.
+    remind_at: Option<DateTime<Utc>>,
+    /// Plain English, such as "next Tuesday 3pm" or "in 2 weeks".
+    when: Option<String>,
+    /// IANA name of the zone `when` is read in, such as `Europe/Berlin`;
+    /// UTC by default.
+    timezone: Option<String>,
+    note: Option<String>,
+}
+
+/// When a new reminder is due. `when` goes through the deterministic
+/// parser, then through the date agent if it's enabled and the parser
+/// didn't understand the phrasing.
+async fn remind_at(
+    state: &AppState,
+    json: &NewReminder,
+) -> Result<DateTime<Utc>, (StatusCode, String)> {
+    let when = match (json.remind_at, json.when.as_deref()) {
+        (Some(at), None) => return Ok(at),
+        (None, Some(when)) => when,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Give either remind_at or when.".to_string(),
+            ))
+        }
+    };
+    let timezone = json.timezone.as_deref().unwrap_or("UTC");
+    let tz: Tz = timezone.parse().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown timezone {timezone:?}."),
+        )
+    })?;
+
+    let now = Utc::now();
+    match natural_date::parse(when, now, &tz) {
+        Ok(at) => Ok(at),
+        Err(e @ DateParseError::Unrecognized(_)) if state.date_agent => {
+            date_agent::resolve(when, now, &tz)
+                .await
+                .map_err(|agent_error| {
+                    warn!("Date agent couldn't resolve {when:?}: {agent_error}");
+                    (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+                })
+        }
+        Err(e) => Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string())),
+    }
+}
+
+/// Schedules a reminder to follow up with a contact, at `remind_at` or at
+/// the time `when` describes. The user then gets a notification and, if they
+/// have an email address, an email.
+pub async fn create_reminder(
+    claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<NewReminder>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let remind_at = remind_at(&state, &json).await?;
+    if remind_at <= Utc::now() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{remind_at} is in the past; reminders must be in the future."),
+        ));
+    }
+    let contact: Option<(i32,)> = sqlx::query_as("SELECT id FROM contacts WHERE id = $1")
+        .bind(json.contact_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db_error)?;
+    if contact.is_none() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("No contact with id {}.", json.contact_id),
+        ));
+    }
+    let note = json
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+
+    // The row is committed before its job is queued, so the job always
+    // finds it; if queueing fails, it's cancelled rather than left to
+    // never fire.
+    let reminder: Reminder = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO reminders (user_id, contact_id, note, remind_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {REMINDER_COLUMNS}
+        "#
+    ))
+    .bind(claims.user_id())
+    .bind(json.contact_id)
+    .bind(note)
+    .bind(remind_at)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    if let Err(e) = follow_up::schedule(&state, reminder.id, reminder.remind_at).await {
+        error!("Unable to schedule reminder {}: {e}", reminder.id);
+        if let Err(e) = sqlx::query("UPDATE reminders SET cancelled_at = NOW() WHERE id = $1")
+            .bind(reminder.id)
+            .execute(&state.db)
+            .await
+        {
+            error!("Unable to cancel unscheduled reminder {}: {e}", reminder.id);
+        }
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Unable to schedule the reminder.".to_string(),
+        ));
+    }
+    info!(
+        "Scheduled reminder {} for user {} at {}",
+        reminder.id,
+        claims.username(),
+        reminder.remind_at
+    );
+
+    Ok((StatusCode::CREATED, Json(reminder)))
+}
+
+/// The current user's reminders that haven't gone out yet, soonest first.
+pub async fn list_reminders(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let reminders: Vec<Reminder> = sqlx::query_as(&format!(
+        r#"
+        SELECT {REMINDER_COLUMNS} FROM reminders
+        WHERE user_id = $1 AND sent_at IS NULL AND cancelled_at IS NULL
+        ORDER BY remind_at, id
+        "#
+    ))
+    .bind(claims.user_id())
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(reminders))
+}
+
+/// Cancels a reminder that hasn't gone out yet; its job finds it cancelled
+/// and does nothing.
+pub async fn cancel_reminder(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let result = sqlx::query(
+        r#"
+        UPDATE reminders SET cancelled_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND sent_at IS NULL AND cancelled_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .bind(claims.user_id())
+    .execute(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err(db_error(sqlx::Error::RowNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,179 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::endpoints::{
+    auth::{Admin, Claims},
+    db_error,
+};
+use crate::jobs::schedules::{parse_cron, JobKind};
+use crate::state::AppState;
+
+const SCHEDULE_COLUMNS: &str =
+    "id, name, kind, cron, paused, next_run_at, last_run_at, created_at, updated_at";
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct JobSchedule {
+    id: i32,
+    name: String,
+    kind: String,
+    cron: String,
+    paused: bool,
+    /// Filled in by the scheduler within a few seconds of a change.
+    next_run_at: Option<DateTime<Utc>>,
+    last_run_at: Option<DateTime<Utc>>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewSchedule {
+    name: String,
+    kind: JobKind,
+    /// Six fields, seconds first: `0 0 7 * * *` is every day at 07:00 UTC.
+    cron: String,
+    #[serde(default)]
+    paused: bool,
+}
+
+pub async fn list_schedules(
+    _claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let schedules: Vec<JobSchedule> = sqlx::query_as(&format!(
+        "SELECT {SCHEDULE_COLUMNS} FROM job_schedules ORDER BY name"
+    ))
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(schedules))
+}
+
+pub async fn get_schedule(
+    _claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let schedule: JobSchedule = sqlx::query_as(&format!(
+        "SELECT {SCHEDULE_COLUMNS} FROM job_schedules WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(schedule))
+}
+
+pub async fn create_schedule(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Json(json): Json<NewSchedule>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let name = json.name.trim();
+    if name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Schedule name must not be empty.".to_string(),
+        ));
+    }
+    let cron = json.cron.trim();
+    parse_cron(cron).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let schedule: JobSchedule = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO job_schedules (name, kind, cron, paused)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {SCHEDULE_COLUMNS}
+        "#
+    ))
+    .bind(name)
+    .bind(json.kind.as_str())
+    .bind(cron)
+    .bind(json.paused)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+    info!(
+        "{} created schedule {} ({})",
+        claims.username(),
+        schedule.id,
+        schedule.name
+    );
+
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+/// Stops a schedule from firing until it's resumed.
+pub async fn pause_schedule(
+    admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    set_paused(admin, state, id, true).await
+}
+
+/// Restarts a paused schedule from its next slot; runs missed while it was
+/// paused are skipped.
+pub async fn resume_schedule(
+    admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    set_paused(admin, state, id, false).await
+}
+
+async fn set_paused(
+    Admin(claims): Admin,
+    state: AppState,
+    id: i32,
+    paused: bool,
+) -> Result<Json<JobSchedule>, (StatusCode, String)> {
+    // Clearing next_run_at has the scheduler recompute it from the cron.
+    let schedule: JobSchedule = sqlx::query_as(&format!(
+        r#"
+        UPDATE job_schedules
+        SET paused = $2, next_run_at = NULL, updated_at = NOW()
+        WHERE id = $1
+        RETURNING {SCHEDULE_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(paused)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+    info!(
+        "{} {} schedule {id}",
+        claims.username(),
+        if paused { "paused" } else { "resumed" }
+    );
+
+    Ok(Json(schedule))
+}
+
+pub async fn delete_schedule(
+    Admin(claims): Admin,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let result = sqlx::query("DELETE FROM job_schedules WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err(db_error(sqlx::Error::RowNotFound));
+    }
+    info!("{} deleted schedule {id}", claims.username());
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,275 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Json,
+};
+use chrono::NaiveDateTime;
+use futures::{stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::io;
+use tracing::{error, info, warn};
+
+use crate::endpoints::{auth::Claims, db_error};
+use crate::jobs::speech::{self, SpeechJobError};
+use crate::services::audio_storage::{AudioStorage, DEFAULT_URL_TTL};
+use crate::services::speech_pipeline::{
+    can_stream, chunk_input, synthesize_stream, RetryPolicy, DEFAULT_CONCURRENCY,
+};
+use crate::services::tts_service::{AudioFormat, SpeechOptions, TtsModel, DEFAULT_VOICE};
+use crate::state::AppState;
+use crate::utils::concat_mp3::concat_mp3_stream;
+
+#[derive(Deserialize)]
+pub struct SpeechRequest {
+    input: String,
+    voice: Option<String>,
+    /// Unknown models and formats are rejected while parsing the body.
+    model: Option<TtsModel>,
+    response_format: Option<AudioFormat>,
+    speed: Option<f32>,
+}
+
+impl SpeechRequest {
+    /// The trimmed input and validated options, or a 400.
+    fn validate(&self) -> Result<(&str, SpeechOptions), (StatusCode, String)> {
+        let input = self.input.trim();
+        if input.is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Input must not be empty.".to_string(),
+            ));
+        }
+
+        let options = SpeechOptions {
+            voice: self
+                .voice
+                .clone()
+                .unwrap_or_else(|| DEFAULT_VOICE.to_string()),
+            model: self.model,
+            format: self.response_format.unwrap_or_default(),
+            speed: self.speed.unwrap_or(1.0),
+        };
+        options
+            .validate()
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+        Ok((input, options))
+    }
+}
+
+/// Where a speech job is at. `audio_url` is set once it's `done`: a signed URL
+/// that expires after [`DEFAULT_URL_TTL`], so poll again for a fresh one.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct SpeechStatus {
+    id: i32,
+    /// `queued`, `running`, `done` or `failed`.
+    status: String,
+    response_format: String,
+    chunks_total: i32,
+    chunks_done: i32,
+    error: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    /// Set when the clip lives in [`AudioStorage`] rather than in its row.
+    #[sqlx(default)]
+    #[serde(skip)]
+    storage_key: Option<String>,
+    /// Length of the finished clip, when it could be measured.
+    #[sqlx(default)]
+    duration_ms: Option<i32>,
+    #[sqlx(skip)]
+    audio_url: Option<String>,
+}
+
+impl SpeechStatus {
+    fn with_audio_url(mut self, storage: &dyn AudioStorage) -> Self {
+        if self.status != "done" {
+            return self;
+        }
+        let signed = self.storage_key.as_deref().and_then(|key| {
+            storage
+                .signed_url(key, DEFAULT_URL_TTL)
+                .inspect_err(|e| warn!("Unable to sign a URL for {key}: {e:#}"))
+                .ok()
+        });
+        self.audio_url = Some(signed.unwrap_or_else(|| format!("/api/speech/{}/audio", self.id)));
+        self
+    }
+}
+
+const STATUS_COLUMNS: &str =
+    "id, status, response_format, chunks_total, chunks_done, error, created_at, updated_at";
+
+/// Queues `input` for background synthesis and answers right away with the
+/// job's status; poll [`get_speech`] until it's `done`.
+pub async fn create_speech(
+    claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<SpeechRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (input, options) = json.validate()?;
+
+    let job: SpeechStatus = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO speech_jobs (user_id, input, voice, model, response_format, speed)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {STATUS_COLUMNS}
+        "#
+    ))
+    .bind(claims.user_id())
+    .bind(input)
+    .bind(&options.voice)
+    .bind(options.model.map(TtsModel::as_str))
+    .bind(options.format.as_str())
+    .bind(options.speed)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    if let Err(e) = speech::enqueue(&state, job.id).await {
+        return Err(match e {
+            SpeechJobError::Db(e) => db_error(e),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Unable to queue speech job.".to_string(),
+            ),
+        });
+    }
+    info!(
+        "Queued speech job {} for user {}",
+        job.id,
+        claims.username()
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Status and chunk progress of one of the current user's speech jobs.
+pub async fn get_speech(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let job: SpeechStatus = sqlx::query_as(&format!(
+        r#"
+        SELECT {STATUS_COLUMNS}, c.storage_key, c.duration_ms
+        FROM speech_jobs
+        LEFT JOIN (SELECT id AS clip_id, storage_key, duration_ms FROM speech_clips) c
+            USING (clip_id)
+        WHERE id = $1 AND user_id = $2
+        "#
+    ))
+    .bind(id)
+    .bind(claims.user_id())
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(job.with_audio_url(state.audio_storage.as_ref())))
+}
+
+/// Synthesizes `input` within the request and streams the joined audio back
+/// while later chunks are still being synthesized. Nothing is stored.
+///
+/// Only MP3, AAC and PCM can be joined on the fly; formats whose header covers
+/// the whole file have to go through [`create_speech`].
+pub async fn stream_speech(
+    claims: Claims,
+    State(state): State<AppState>,
+    Json(json): Json<SpeechRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (input, options) = json.validate()?;
+    let format = options.format;
+    if !can_stream(format) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{format} audio can't be streamed; use POST /api/speech instead."),
+        ));
+    }
+
+    let chunks = chunk_input(input, &state.normalizer, state.chunk_limit);
+    info!(
+        "Streaming {} chunk(s) for user {}",
+        chunks.len(),
+        claims.username()
+    );
+
+    let audio = synthesize_stream(
+        state.tts.clone(),
+        chunks,
+        options,
+        DEFAULT_CONCURRENCY,
+        RetryPolicy::default(),
+    )
+    .map_err(|e| {
+        error!("TTS failed while streaming: {e}");
+        io::Error::other(e)
+    })
+    .map_ok(|chunk| stream::iter(chunk.into_parts().map(Ok::<_, io::Error>)))
+    .try_flatten();
+
+    // ADTS AAC and raw PCM chunks can be sent exactly as they arrive.
+    let body = match format {
+        AudioFormat::Mp3 => Body::from_stream(concat_mp3_stream(audio)),
+        _ => Body::from_stream(audio),
+    };
+
+    Ok(([(header::CONTENT_TYPE, format.content_type())], body))
+}
+
+/// The audio of a finished job: a redirect to its signed storage URL, or the
+/// bytes themselves for clips stored before [`AudioStorage`]. Users can only
+/// download their own audio.
+pub async fn get_speech_audio(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Response, (StatusCode, String)> {
+    let (audio, storage_key, format): (Option<Vec<u8>>, Option<String>, String) = sqlx::query_as(
+        r#"
+            SELECT c.audio, c.storage_key, c.response_format
+            FROM speech_jobs j
+            JOIN speech_clips c ON c.id = j.clip_id
+            WHERE j.id = $1 AND j.user_id = $2
+            "#,
+    )
+    .bind(id)
+    .bind(claims.user_id())
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    if let Some(key) = storage_key {
+        let url = state
+            .audio_storage
+            .signed_url(&key, DEFAULT_URL_TTL)
+            .map_err(|e| {
+                error!("Unable to sign a URL for {key}: {e:#}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Unable to sign the audio URL.".to_string(),
+                )
+            })?;
+        return Ok(Redirect::temporary(&url).into_response());
+    }
+
+    let format: AudioFormat = format.parse().unwrap_or_default();
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"speech-{id}.{}\"", format.extension()),
+            ),
+        ],
+        audio.unwrap_or_default(),
+    )
+        .into_response())
+}
+
+/// Hit/miss counters of the TTS cache since startup.
+pub async fn cache_stats(_claims: Claims, State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tts_cache_metrics.snapshot())
+}
@@ -0,0 +1,394 @@
+// src/jobs/briefing.rs
+//! The daily spoken briefing. Every morning each user gets a short script
+//! about the contacts added and companies enriched since yesterday; it's
+//! queued as a regular speech job and published in the user's podcast feed
+//! (see `src/endpoints/briefings.rs`).
+//!
+//! The CRM has no tasks yet, so there's no "due today" section.
+
+use apalis::prelude::Data;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+use crate::jobs::registry::{Job, JobError, JobSpec};
+use crate::jobs::speech::{self, SpeechJobError};
+use crate::services::tts_service::DEFAULT_VOICE;
+use crate::state::AppState;
+use crate::utils::backoff::{Backoff, Jitter};
+
+/// How many contacts or companies are named before the rest is just counted.
+const MAX_NAMED: usize = 5;
+
+/// Fired by a schedule; the timestamp is when it was due.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct DailyBriefing(DateTime<Utc>);
+
+/// Users who already have the day's briefing are skipped, so retries are safe.
+impl Job for DailyBriefing {
+    const SPEC: JobSpec = JobSpec {
+        worker: "daily-briefing",
+        namespace: "briefing::DailyBriefing",
+        concurrency: 1,
+        retries: 3,
+        backoff: Backoff {
+            base: Duration::from_secs(30),
+            max: Duration::from_secs(10 * 60),
+            factor: 2,
+            jitter: Jitter::Equal,
+        },
+    };
+}
+
+impl From<DateTime<Utc>> for DailyBriefing {
+    fn from(t: DateTime<Utc>) -> Self {
+        DailyBriefing(t)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BriefingError {
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+    #[error(transparent)]
+    Speech(#[from] SpeechJobError),
+    #[error("{failed} of {users} briefing(s) failed")]
+    Incomplete { failed: usize, users: usize },
+}
+
+impl JobError for BriefingError {
+    fn is_permanent(&self) -> bool {
+        match self {
+            BriefingError::Db(_) | BriefingError::Incomplete { .. } => false,
+            BriefingError::Speech(e) => e.is_permanent(),
+        }
+    }
+}
+
+/// What happened in the CRM over the briefing's window.
+#[derive(Debug, Default)]
+pub struct Digest {
+    /// `(first name, last name, company)` of the newest contacts.
+    pub new_contacts: Vec<(String, String, String)>,
+    pub new_contacts_total: i64,
+    /// `(name, industry)` of the most recently enriched companies.
+    pub enriched_companies: Vec<(String, Option<String>)>,
+    pub enriched_companies_total: i64,
+}
+
+impl Digest {
+    pub async fn since(db: &sqlx::PgPool, since: NaiveDateTime) -> Result<Self, sqlx::Error> {
+        let new_contacts = sqlx::query_as(
+            r#"
+            SELECT first_name, last_name, company FROM contacts
+            WHERE created_at >= $1
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(since)
+        .bind(MAX_NAMED as i64)
+        .fetch_all(db)
+        .await?;
+        let (new_contacts_total,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM contacts WHERE created_at >= $1")
+                .bind(since)
+                .fetch_one(db)
+                .await?;
+
+        let enriched_companies = sqlx::query_as(
+            r#"
+            SELECT name, industry FROM companies
+            WHERE enriched_at >= $1
+            ORDER BY enriched_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(since)
+        .bind(MAX_NAMED as i64)
+        .fetch_all(db)
+        .await?;
+        let (enriched_companies_total,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM companies WHERE enriched_at >= $1")
+                .bind(since)
+                .fetch_one(db)
+                .await?;
+
+        Ok(Self {
+            new_contacts,
+            new_contacts_total,
+            enriched_companies,
+            enriched_companies_total,
+        })
+    }
+}
+
+pub fn briefing_title(date: NaiveDate) -> String {
+    format!("Briefing for {}", date.format("%A, %B %-d, %Y"))
+}
+
+/// The script read out to `username`. Sections are separate paragraphs, so
+/// the speech pipeline puts a pause between them.
+pub fn briefing_script(username: &str, date: NaiveDate, digest: &Digest) -> String {
+    let mut paragraphs = vec![format!(
+        "Good morning, {username}. Here is your briefing for {}.",
+        date.format("%A, %B %-d")
+    )];
+
+    paragraphs.push(match digest.new_contacts_total {
+        0 => "No new contacts were added since yesterday.".to_string(),
+        total => {
+            let named: Vec<String> = digest
+                .new_contacts
+                .iter()
+                .map(|(first, last, company)| format!("{first} {last} from {company}"))
+                .collect();
+            format!(
+                "{} since yesterday: {}{}.",
+                plural(total, "new contact", "new contacts"),
+                list(&named),
+                more(total, named.len())
+            )
+        }
+    });
+
+    paragraphs.push(match digest.enriched_companies_total {
+        0 => "No companies were enriched since yesterday.".to_string(),
+        total => {
+            let named: Vec<String> = digest
+                .enriched_companies
+                .iter()
+                .map(|(name, industry)| match industry {
+                    Some(industry) => format!("{name}, in {industry}"),
+                    None => name.clone(),
+                })
+                .collect();
+            format!(
+                "{} enriched: {}{}.",
+                plural(total, "company", "companies"),
+                list(&named),
+                more(total, named.len())
+            )
+        }
+    });
+
+    paragraphs.push("That's all for today. Have a great day!".to_string());
+    paragraphs.join("\n\n")
+}
+
+fn plural(n: i64, one: &str, many: &str) -> String {
+    if n == 1 {
+        format!("1 {one}")
+    } else {
+        format!("{n} {many}")
+    }
+}
+
+/// "a", "a and b", "a, b and c".
+fn list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+fn more(total: i64, named: usize) -> String {
+    match total - named as i64 {
+        n if n > 0 => format!(", plus {n} more"),
+        _ => String::new(),
+    }
+}
+
+/// Worker entry point: one briefing per user for the scheduled day. Users
+/// who already have one for that day are skipped, so reruns are harmless;
+/// the job fails if any user's briefing did, and its retry picks them up.
+pub async fn run_daily_briefing(
+    job: DailyBriefing,
+    state: Data<AppState>,
+) -> Result<(), BriefingError> {
+    let date = job.0.date_naive();
+    let digest = Digest::since(&state.db, (job.0 - TimeDelta::days(1)).naive_utc()).await?;
+    let users: Vec<(i32, String)> = sqlx::query_as("SELECT id, username FROM users ORDER BY id")
+        .fetch_all(&state.db)
+        .await?;
+
+    let (mut queued, mut failed) = (0, 0);
+    for (user_id, username) in &users {
+        match queue_briefing(&state, *user_id, username, date, &digest).await {
+            Ok(true) => queued += 1,
+            Ok(false) => {}
+            Err(e) => {
+                error!("Briefing for user {user_id} failed: {e}");
+                failed += 1;
+            }
+        }
+    }
+    info!("Queued {queued} briefing(s) for {date}");
+    if failed > 0 {
+        return Err(BriefingError::Incomplete {
+            failed,
+            users: users.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Records the day's briefing and queues its audio; `false` when the user
+/// already has one. A briefing whose audio never made it onto the queue
+/// (the push failed, or the process died before it) is queued again.
+async fn queue_briefing(
+    state: &AppState,
+    user_id: i32,
+    username: &str,
+    date: NaiveDate,
+    digest: &Digest,
+) -> Result<bool, BriefingError> {
+    let script = briefing_script(username, date, digest);
+
+    let mut tx = state.db.begin().await?;
+    let briefing: Option<(i32,)> = sqlx::query_as(
+        r#"
+        INSERT INTO briefings (user_id, briefing_date, title, script)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, briefing_date) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(date)
+    .bind(briefing_title(date))
+    .bind(&script)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((briefing_id,)) = briefing else {
+        return requeue_briefing(state, user_id, date).await;
+    };
+
+    let (job_id,): (i32,) = sqlx::query_as(
+        "INSERT INTO speech_jobs (user_id, input, voice) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(&script)
+    .bind(DEFAULT_VOICE)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE briefings SET speech_job_id = $2 WHERE id = $1")
+        .bind(briefing_id)
+        .bind(job_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    speech::push(state, job_id).await?;
+    Ok(true)
+}
+
+/// Queues the audio of an existing briefing if it isn't on the queue yet.
+async fn requeue_briefing(
+    state: &AppState,
+    user_id: i32,
+    date: NaiveDate,
+) -> Result<bool, BriefingError> {
+    let pending: Option<(i32,)> = sqlx::query_as(
+        r#"
+        SELECT s.id FROM briefings b
+        JOIN speech_jobs s ON s.id = b.speech_job_id
+        WHERE b.user_id = $1 AND b.briefing_date = $2 AND s.status = 'queued'
+        "#,
+    )
+    .bind(user_id)
+    .bind(date)
+    .fetch_optional(&state.db)
+    .await?;
+    let Some((job_id,)) = pending else {
+        return Ok(false);
+    };
+    if speech::is_queued(&state.db, job_id).await? {
+        return Ok(false);
+    }
+
+    warn!("Briefing audio for user {user_id} on {date} was never queued; queuing it now.");
+    speech::push(state, job_id).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+    }
+
+    fn contact(i: usize) -> (String, String, String) {
+        (
+            format!("First{i}"),
+            format!("Last{i}"),
+            format!("Company{i}"),
+        )
+    }
+
+    #[test]
+    fn empty_digest() {
+        assert_eq!(
+            briefing_script("ada", date(), &Digest::default()),
+            "Good morning, ada. Here is your briefing for Monday, October 19.\n\n\
+             No new contacts were added since yesterday.\n\n\
+             No companies were enriched since yesterday.\n\n\
+             That's all for today. Have a great day!"
+        );
+    }
+
+    #[test]
+    fn single_items_are_singular() {
+        let digest = Digest {
+            new_contacts: vec![contact(1)],
+            new_contacts_total: 1,
+            enriched_companies: vec![("Acme".to_string(), None)],
+            enriched_companies_total: 1,
+        };
+        let script = briefing_script("ada", date(), &digest);
+
+        assert!(
+            script.contains("\n\n1 new contact since yesterday: First1 Last1 from Company1.\n\n")
+        );
+        assert!(script.contains("\n\n1 company enriched: Acme.\n\n"));
+    }
+
+    #[test]
+    fn names_at_most_max_named_and_counts_the_rest() {
+        let digest = Digest {
+            new_contacts: (1..=MAX_NAMED).map(contact).collect(),
+            new_contacts_total: 8,
+            enriched_companies: vec![
+                ("Acme".to_string(), Some("Robotics".to_string())),
+                ("Globex".to_string(), None),
+            ],
+            enriched_companies_total: 2,
+        };
+        let script = briefing_script("ada", date(), &digest);
+
+        assert!(script.contains(
+            "8 new contacts since yesterday: First1 Last1 from Company1, \
+             First2 Last2 from Company2, First3 Last3 from Company3, \
+             First4 Last4 from Company4 and First5 Last5 from Company5, plus 3 more."
+        ));
+        assert!(script.contains("2 companies enriched: Acme, in Robotics and Globex."));
+    }
+
+    #[test]
+    fn lists_read_naturally() {
+        let items = |n: usize| (1..=n).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(list(&items(0)), "");
+        assert_eq!(list(&items(1)), "1");
+        assert_eq!(list(&items(2)), "1 and 2");
+        assert_eq!(list(&items(3)), "1, 2 and 3");
+        assert_eq!(plural(0, "company", "companies"), "0 companies");
+        assert_eq!(more(5, 5), "");
+        assert_eq!(more(6, 5), ", plus 1 more");
+    }
+}
@@ -0,0 +1,122 @@
+// src/jobs/dead_letter.rs
+//! Retries and the dead-letter queue. Every registered worker runs its
+//! handler through [`perform`], which retries a failed job with the backoff
+//! its [`JobSpec`](crate::jobs::registry::JobSpec) declares; a job that fails
+//! every attempt, or fails permanently, is kept in `dead_jobs` with its
+//! payload and error chain until an admin requeues or discards it (see
+//! `src/endpoints/dead_jobs.rs`).
+//!
+//! The backoff sleeps inside the handler, so a retrying job keeps its
+//! worker slot (and its place in the queue) until it's done.
+
+use apalis::prelude::{Data, TaskId};
+use sqlx::PgPool;
+use std::{error::Error, future::Future};
+use tracing::{error, warn};
+
+use crate::jobs::registry::{InFlight, Job, JobError};
+
+/// Runs `handler` for `job`, retrying failures up to `J::SPEC.retries`
+/// times unless they're [permanent](JobError::is_permanent). The last error
+/// is buried in `dead_jobs` and handed back to apalis.
+///
+/// The job is in `in_flight` until this returns; if shutdown drops it
+/// first, it stays there to be requeued.
+pub async fn perform<J, D, F, Fut, E>(
+    job: J,
+    task_id: TaskId,
+    data: Data<D>,
+    db: Data<PgPool>,
+    in_flight: Data<InFlight>,
+    handler: F,
+) -> Result<(), E>
+where
+    J: Job,
+    Data<D>: Clone,
+    F: Fn(J, Data<D>) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: JobError,
+{
+    in_flight.start(&task_id);
+    let result = attempts(job, data, &db, handler).await;
+    in_flight.finish(&task_id);
+    result
+}
+
+async fn attempts<J, D, F, Fut, E>(job: J, data: Data<D>, db: &PgPool, handler: F) -> Result<(), E>
+where
+    J: Job,
+    Data<D>: Clone,
+    F: Fn(J, Data<D>) -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: JobError,
+{
+    let spec = J::SPEC;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let e = match handler(job.clone(), data.clone()).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        if e.is_permanent() {
+            warn!("{} attempt {attempt} failed permanently: {e}", spec.worker);
+        } else if attempt <= spec.retries {
+            let delay = spec.backoff.jittered_delay(attempt as u32 - 1);
+            warn!(
+                "{} attempt {attempt} failed, retrying in {delay:?}: {e}",
+                spec.worker
+            );
+            tokio::time::sleep(delay).await;
+            continue;
+        }
+
+        if let Err(bury_error) = bury(db, &job, attempt, &e).await {
+            error!(
+                "Unable to dead-letter a {} job ({e}): {bury_error}",
+                spec.worker
+            );
+        }
+        return Err(e);
+    }
+}
+
+/// Records a job that has run out of attempts.
+pub async fn bury<J: Job>(
+    db: &PgPool,
+    job: &J,
+    attempts: usize,
+    e: &(dyn Error + Send + Sync + 'static),
+) -> Result<i32, sqlx::Error> {
+    let payload = serde_json::to_value(job).map_err(|e| sqlx::Error::Encode(e.into()))?;
+    let chain = error_chain(e);
+    error!(
+        "{} job failed {attempts} time(s), dead-lettered: {}",
+        J::SPEC.worker,
+        chain.join(": ")
+    );
+
+    let (id,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO dead_jobs (worker, namespace, payload, error_chain, attempts)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(J::SPEC.worker)
+    .bind(J::SPEC.namespace)
+    .bind(payload)
+    .bind(&chain)
+    .bind(attempts as i32)
+    .fetch_one(db)
+    .await?;
+
+    Ok(id)
+}
+
+/// `e` followed by each of its sources.
+pub fn error_chain(e: &(dyn Error + 'static)) -> Vec<String> {
+    std::iter::successors(Some(e), |&e| e.source())
+        .map(ToString::to_string)
+        .collect()
+}
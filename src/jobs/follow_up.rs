@@ -0,0 +1,168 @@
+// src/jobs/follow_up.rs
+//! Follow-up reminders users set on their contacts. `POST /api/reminders`
+//! records a `reminders` row and schedules a [`FollowUpReminder`] for its
+//! time; when it fires, the user gets an in-app notification about the
+//! contact and, if they gave an email address, an email.
+
+use apalis::prelude::{Data, Storage};
+use chrono::{DateTime, Utc};
+use resend_rs::types::CreateEmailBaseOptions;
+use resend_rs::Resend;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::jobs::registry::{Job, JobError, JobSpec};
+use crate::jobs::reminder::EMAIL_FROM;
+use crate::state::AppState;
+use crate::utils::backoff::{Backoff, Jitter};
+
+/// Queue payload; everything else lives on the `reminders` row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowUpReminder {
+    pub reminder_id: i32,
+}
+
+/// Reminders already sent or cancelled are skipped, so retries are safe.
+impl Job for FollowUpReminder {
+    const SPEC: JobSpec = JobSpec {
+        worker: "follow-up",
+        namespace: "follow_up::FollowUpReminder",
+        concurrency: 2,
+        retries: 4,
+        backoff: Backoff {
+            base: Duration::from_secs(10),
+            max: Duration::from_secs(5 * 60),
+            factor: 3,
+            jitter: Jitter::Full,
+        },
+    };
+}
+
+#[derive(Debug, Error)]
+pub enum FollowUpError {
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+    #[error("Unable to send the reminder email")]
+    Email(#[source] resend_rs::Error),
+    #[error("Unable to queue job: {0}")]
+    Queue(String),
+}
+
+impl JobError for FollowUpError {}
+
+/// Schedules the job for an inserted `reminders` row to run at `at`.
+pub async fn schedule(
+    state: &AppState,
+    reminder_id: i32,
+    at: DateTime<Utc>,
+) -> Result<(), FollowUpError> {
+    let mut queue = state.follow_ups.clone();
+    queue
+        .schedule(FollowUpReminder { reminder_id }, at.timestamp())
+        .await
+        .map_err(|e| FollowUpError::Queue(e.to_string()))?;
+    Ok(())
+}
+
+/// A reminder that's due, with what the notification and email need.
+#[derive(Debug, sqlx::FromRow)]
+struct DueReminder {
+    user_id: i32,
+    email: Option<String>,
+    contact_id: i32,
+    first_name: String,
+    last_name: String,
+    company: String,
+    note: Option<String>,
+}
+
+impl DueReminder {
+    fn title(&self) -> String {
+        format!("Follow up with {} {}", self.first_name, self.last_name)
+    }
+
+    fn body(&self) -> String {
+        let mut body = format!(
+            "It's time to follow up with {} {} ({}).",
+            self.first_name, self.last_name, self.company
+        );
+        if let Some(note) = self.note.as_deref().filter(|note| !note.is_empty()) {
+            body.push_str("\n\n");
+            body.push_str(note);
+        }
+        body
+    }
+}
+
+/// Worker entry point. The row stays locked until the notification and the
+/// email are out, so a duplicate job waits and then finds it sent.
+pub async fn run_follow_up(
+    job: FollowUpReminder,
+    state: Data<AppState>,
+) -> Result<(), FollowUpError> {
+    let mut tx = state.db.begin().await?;
+
+    let due: Option<DueReminder> = sqlx::query_as(
+        r#"
+        SELECT r.user_id, u.email, r.contact_id,
+            c.first_name, c.last_name, c.company, r.note
+        FROM reminders r
+        JOIN users u ON u.id = r.user_id
+        JOIN contacts c ON c.id = r.contact_id
+        WHERE r.id = $1 AND r.sent_at IS NULL AND r.cancelled_at IS NULL
+        FOR UPDATE OF r
+        "#,
+    )
+    .bind(job.reminder_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(due) = due else {
+        info!(
+            "Reminder {} was sent, cancelled or deleted; skipping.",
+            job.reminder_id
+        );
+        return Ok(());
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO notifications (user_id, title, body, contact_id)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(due.user_id)
+    .bind(due.title())
+    .bind(due.body())
+    .bind(due.contact_id)
+    .execute(&mut *tx)
+    .await?;
+
+    match due.email.as_deref() {
+        Some(to) => {
+            // No link: the contact lives behind the API's session cookie,
+            // which a mail client doesn't have.
+            let email =
+                CreateEmailBaseOptions::new(EMAIL_FROM, [to], due.title()).with_text(&due.body());
+            Resend::default()
+                .emails
+                .send(email)
+                .await
+                .map_err(FollowUpError::Email)?;
+        }
+        None => warn!(
+            "User {} has no email address; reminder {} is only a notification.",
+            due.user_id, job.reminder_id
+        ),
+    }
+
+    sqlx::query("UPDATE reminders SET sent_at = NOW() WHERE id = $1")
+        .bind(job.reminder_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    info!("Reminder {} sent to user {}", job.reminder_id, due.user_id);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+// src/jobs/idempotency.rs
+//! At-most-once side effects for scheduled jobs. The same tick can reach a
+//! worker more than once: a job re-run after a crash, a requeued dead job,
+//! two instances racing. Each side effect is keyed by the job kind and the
+//! time it was scheduled for, and the key is only marked done once the
+//! effect has succeeded. Keys are pruned by the hourly retention sweep once their
+//! tick is too old to be replayed.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::{future::Future, time::Duration};
+use tracing::{info, warn};
+
+/// How long after its tick a key is kept. Retries give up within minutes;
+/// this also leaves a month to requeue a dead job without repeating it.
+pub const KEY_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How long a claimed key belongs to the worker running its effect. A
+/// worker that crashes mid-effect never marks its key done; after this,
+/// another attempt may take the key over. Comfortably longer than any
+/// effect should take.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(10 * 60);
+
+/// Runs `effect` unless it already ran (or is running) for
+/// `(kind, scheduled_at)`, and returns `None` when it's skipped.
+///
+/// The key is claimed as `pending` and marked `done` in two short
+/// statements around `effect`, so no connection is held while it runs. A
+/// concurrent duplicate finds the claim and skips; a failed `effect`
+/// releases it for the next attempt. Only a crash after the effect but
+/// before the key is marked done can repeat it, once the lease is up.
+pub async fn once<T, E, Fut>(
+    db: &PgPool,
+    kind: &str,
+    scheduled_at: DateTime<Utc>,
+    effect: impl FnOnce() -> Fut,
+) -> Result<Option<T>, E>
+where
+    Fut: Future<Output = Result<T, E>>,
+    E: From<sqlx::Error>,
+{
+    // A fresh key, or a pending one whose worker's lease ran out. The claim
+    // time identifies this claim when it is released.
+    let claim: Option<(DateTime<Utc>,)> = sqlx::query_as(
+        r#"
+        INSERT INTO job_idempotency_keys (kind, scheduled_at, status, claimed_at)
+        VALUES ($1, $2, 'pending', NOW())
+        ON CONFLICT (kind, scheduled_at) DO UPDATE SET claimed_at = NOW()
+        WHERE job_idempotency_keys.status = 'pending'
+            AND job_idempotency_keys.claimed_at < NOW() - make_interval(secs => $3)
+        RETURNING claimed_at
+        "#,
+    )
+    .bind(kind)
+    .bind(scheduled_at)
+    .bind(CLAIM_LEASE.as_secs_f64())
+    .fetch_optional(db)
+    .await?;
+    let Some((claimed_at,)) = claim else {
+        info!("Skipping {kind} for {scheduled_at}: it already ran or is running.");
+        return Ok(None);
+    };
+
+    let output = match effect().await {
+        Ok(output) => output,
+        Err(e) => {
+            let released = sqlx::query(
+                r#"
+                DELETE FROM job_idempotency_keys
+                WHERE kind = $1 AND scheduled_at = $2 AND status = 'pending' AND claimed_at = $3
+                "#,
+            )
+            .bind(kind)
+            .bind(scheduled_at)
+            .bind(claimed_at)
+            .execute(db)
+            .await;
+            if let Err(release_error) = released {
+                warn!(
+                    "Unable to release {kind} for {scheduled_at}; it can be retried once its lease is up: {release_error}"
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE job_idempotency_keys SET status = 'done', completed_at = NOW()
+        WHERE kind = $1 AND scheduled_at = $2
+        "#,
+    )
+    .bind(kind)
+    .bind(scheduled_at)
+    .execute(db)
+    .await?;
+    Ok(Some(output))
+}
+
+/// Deletes the keys of ticks scheduled more than [`KEY_RETENTION`] ago and
+/// returns how many there were.
+pub async fn prune(db: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM job_idempotency_keys WHERE scheduled_at < NOW() - make_interval(secs => $1)",
+    )
+    .bind(KEY_RETENTION.as_secs_f64())
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,9 @@
+pub mod briefing;
+pub mod dead_letter;
+pub mod follow_up;
+pub mod idempotency;
+pub mod registry;
+pub mod reminder;
+pub mod retention;
+pub mod schedules;
+pub mod speech;
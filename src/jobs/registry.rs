@@ -0,0 +1,264 @@
+// src/jobs/registry.rs
+//! Every kind of queued background job and how its worker runs. A payload
+//! type implements [`Job`] to declare its queue namespace, concurrency and
+//! retry policy, and gets one line in [`register_workers`], which starts all
+//! of them under a single apalis [`Monitor`]. Handlers run through
+//! [`dead_letter::perform`], which does the retrying.
+//!
+//! Worker ids carry an instance id that's new on every boot, so the jobs an
+//! instance holds can be told apart from another's, and [`InFlight`] tracks
+//! which of them are still running: on shutdown, [`requeue_unfinished`]
+//! hands back the ones it didn't get to finish.
+//!
+//! Registered today: the reminder email, users' follow-up reminders, daily
+//! briefings and speech (TTS) generation. Contact enrichment and dedupe are
+//! not registered: there are no such jobs to run yet. Each will be a payload
+//! type, a [`Job`] impl and a line here.
+
+use apalis::prelude::{
+    Data, Monitor, Storage, TaskId, WorkerBuilder, WorkerBuilderExt, WorkerFactoryFn,
+};
+use apalis_cron::{CronStream, Schedule};
+use apalis_sql::{postgres::PostgresStorage, Config};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::{
+    collections::HashSet,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+use tracing::info;
+
+use crate::jobs::briefing::{run_daily_briefing, DailyBriefing};
+use crate::jobs::dead_letter;
+use crate::jobs::follow_up::{run_follow_up, FollowUpReminder};
+use crate::jobs::reminder::{say_hello_world, CronjobData, Reminder};
+use crate::jobs::retention::{run_retention_sweep, AudioRetention};
+use crate::jobs::speech::{run_speech_job, SpeechJob};
+use crate::state::AppState;
+use crate::utils::backoff::Backoff;
+
+/// How a kind of job is queued and worked.
+#[derive(Debug, Clone, Copy)]
+pub struct JobSpec {
+    /// Worker name, as it shows up in the logs.
+    pub worker: &'static str,
+    /// apalis namespace of the job's queue.
+    pub namespace: &'static str,
+    /// Jobs of this kind run at once on each instance.
+    pub concurrency: usize,
+    /// Attempts after the first failure.
+    pub retries: usize,
+    /// Wait before each of those attempts.
+    pub backoff: Backoff,
+}
+
+/// A queue payload with its worker settings.
+pub trait Job: Serialize + DeserializeOwned + Clone + Send + Sync + Unpin + 'static {
+    const SPEC: JobSpec;
+}
+
+/// The error a job handler fails with.
+pub trait JobError: std::error::Error + Send + Sync + 'static {
+    /// Errors that another attempt can't fix, such as malformed input;
+    /// the job goes straight to the dead-letter queue.
+    fn is_permanent(&self) -> bool {
+        false
+    }
+}
+
+/// The queue of `J`; workers, the scheduler and endpoints all push and pull
+/// through one of these.
+pub fn storage<J: Job>(db: &PgPool) -> PostgresStorage<J> {
+    PostgresStorage::new_with_config(db.clone(), Config::new(J::SPEC.namespace))
+}
+
+#[derive(Debug, Error)]
+pub enum RequeueError {
+    #[error("No job is registered under {0}")]
+    UnknownNamespace(String),
+    #[error("Payload doesn't fit a {worker} job: {source}")]
+    Payload {
+        worker: &'static str,
+        source: serde_json::Error,
+    },
+    #[error("Unable to queue the job: {0}")]
+    Queue(#[from] sqlx::Error),
+}
+
+/// Pushes a stored payload back onto the queue called `namespace`, for
+/// requeued dead jobs.
+pub async fn requeue(db: &PgPool, namespace: &str, payload: Value) -> Result<(), RequeueError> {
+    async fn push<J: Job>(db: &PgPool, payload: Value) -> Result<(), RequeueError> {
+        let job: J = serde_json::from_value(payload).map_err(|source| RequeueError::Payload {
+            worker: J::SPEC.worker,
+            source,
+        })?;
+        storage::<J>(db).push(job).await?;
+        Ok(())
+    }
+
+    match namespace {
+        n if n == Reminder::SPEC.namespace => push::<Reminder>(db, payload).await,
+        n if n == FollowUpReminder::SPEC.namespace => push::<FollowUpReminder>(db, payload).await,
+        n if n == DailyBriefing::SPEC.namespace => push::<DailyBriefing>(db, payload).await,
+        n if n == SpeechJob::SPEC.namespace => push::<SpeechJob>(db, payload).await,
+        other => Err(RequeueError::UnknownNamespace(other.to_string())),
+    }
+}
+
+/// Ids of the jobs this instance's handlers have started and not finished.
+/// apalis only acks a finished job on its next heartbeat, so after a
+/// shutdown a job that did finish can still show as `Running`; this is what
+/// tells the two apart.
+#[derive(Debug, Clone, Default)]
+pub struct InFlight(Arc<Mutex<HashSet<String>>>);
+
+impl InFlight {
+    pub fn start(&self, task_id: &TaskId) {
+        self.ids().insert(task_id.to_string());
+    }
+
+    pub fn finish(&self, task_id: &TaskId) {
+        self.ids().remove(&task_id.to_string());
+    }
+
+    fn ids(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The id `spec`'s worker locks its jobs with on `instance`.
+fn worker_id(spec: &JobSpec, instance: &str) -> String {
+    format!("{}@{instance}", spec.worker)
+}
+
+/// Puts the jobs that `instance`'s workers were still running back on their
+/// queues, once those workers have stopped. Returns how many there were.
+pub async fn requeue_unfinished(
+    db: &PgPool,
+    instance: &str,
+    in_flight: &InFlight,
+) -> Result<u64, sqlx::Error> {
+    let unfinished: Vec<String> = in_flight.ids().iter().cloned().collect();
+    if unfinished.is_empty() {
+        return Ok(0);
+    }
+    let workers: Vec<String> = [
+        Reminder::SPEC,
+        FollowUpReminder::SPEC,
+        DailyBriefing::SPEC,
+        SpeechJob::SPEC,
+    ]
+    .iter()
+    .map(|spec| worker_id(spec, instance))
+    .collect();
+    let result = sqlx::query(
+        r#"
+        UPDATE apalis.jobs
+        SET status = 'Pending', lock_by = NULL, lock_at = NULL, done_at = NULL,
+            last_error = 'Interrupted by shutdown'
+        WHERE status = 'Running' AND lock_by = ANY($1) AND id = ANY($2)
+        "#,
+    )
+    .bind(&workers)
+    .bind(&unfinished)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Builds the worker of a registered job from its [`JobSpec`].
+macro_rules! worker {
+    ($job:ty, $db:expr, $instance:expr, $in_flight:expr, $data:expr, $handler:expr) => {{
+        let spec = <$job as Job>::SPEC;
+        WorkerBuilder::new(worker_id(&spec, $instance))
+            .concurrency(spec.concurrency)
+            .data($data)
+            .data($db.clone())
+            .data($in_flight.clone())
+            .backend(storage::<$job>($db))
+            .build_fn(
+                |job: $job,
+                 task_id: TaskId,
+                 data: Data<_>,
+                 db: Data<PgPool>,
+                 in_flight: Data<InFlight>| {
+                    dead_letter::perform(job, task_id, data, db, in_flight, $handler)
+                },
+            )
+    }};
+}
+
+/// One monitor running every registered worker as `instance`, plus the
+/// hourly retention sweep (which only deletes audio when `audio_retention`
+/// is set). The workers' jobs are tracked in `in_flight`.
+pub fn register_workers(
+    state: &AppState,
+    instance: &str,
+    in_flight: &InFlight,
+    audio_retention: Option<Duration>,
+) -> Monitor {
+    let db = &state.db;
+    let reminder_data = CronjobData {
+        message: "Hello world".to_string(),
+        db: db.clone(),
+    };
+
+    let monitor = Monitor::new()
+        .register(worker!(
+            Reminder,
+            db,
+            instance,
+            in_flight,
+            reminder_data,
+            say_hello_world
+        ))
+        .register(worker!(
+            FollowUpReminder,
+            db,
+            instance,
+            in_flight,
+            state.clone(),
+            run_follow_up
+        ))
+        .register(worker!(
+            DailyBriefing,
+            db,
+            instance,
+            in_flight,
+            state.clone(),
+            run_daily_briefing
+        ))
+        .register(worker!(
+            SpeechJob,
+            db,
+            instance,
+            in_flight,
+            state.clone(),
+            run_speech_job
+        ));
+
+    // Housekeeping rather than a queue: an hourly cron stream.
+    let schedule =
+        Schedule::from_str("0 0 * * * *").expect("Couldn't create the retention schedule!");
+    let retention_worker = WorkerBuilder::new("retention")
+        .data(AudioRetention {
+            db: state.db.clone(),
+            storage: state.audio_storage.clone(),
+            max_age: audio_retention,
+        })
+        .backend(CronStream::new(schedule))
+        .build_fn(run_retention_sweep);
+    if let Some(max_age) = audio_retention {
+        info!("Deleting speech audio older than {max_age:?}.");
+    }
+
+    monitor.register(retention_worker)
+}
@@ -0,0 +1,293 @@
+// src/jobs/reminder.rs
+//! The reminder job: asks an agent for a joke and emails it. Fired by the
+//! schedules in `job_schedules` (see [`crate::jobs::schedules`]). Each
+//! scheduled time is emailed at most once, however often its job runs (see
+//! [`crate::jobs::idempotency`]).
+
+use apalis::prelude::Data;
+use chrono::{DateTime, Utc};
+use resend_rs::types::CreateEmailBaseOptions;
+use resend_rs::Resend;
+use rig::{
+    completion::{Prompt, PromptError, ToolDefinition},
+    providers,
+    tool::Tool,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{env, time::Duration};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+
+use crate::jobs::idempotency;
+use crate::jobs::registry::{Job, JobError, JobSpec};
+use crate::utils::backoff::{Backoff, Jitter};
+
+const JOKE_AGENT_PREAMBLE: &str = r#"
+You are a humorous assistant that generates:
+1. A creative, funny email subject about a random topic
+2. A joke that matches the subject
+Respond ONLY in valid JSON format with:
+{ 
+  "subject": "your funny subject here",
+  "body": "your joke here (keep it work-appropriate)"
+}"#;
+
+/// Sender of every email the jobs send; must be a verified sender/domain in Resend.
+pub const EMAIL_FROM: &str = "Acme <onboarding@resend.dev>";
+
+#[derive(Clone)]
+pub struct CronjobData {
+    pub message: String,
+    pub db: PgPool,
+}
+
+impl CronjobData {
+    fn execute(&self, _item: Reminder) {
+        println!("{} from CronjobData::execute()!", &self.message);
+        info!("CronjobData::execute() finished for item: {:?}", _item);
+    }
+}
+
+/// A custom error for the email-sending tool.
+#[derive(Error, Debug)]
+#[error("Email error: {0}")]
+struct EmailError(String);
+
+/// The arguments our "send_email" tool accepts.
+#[derive(Deserialize, Serialize, Debug)]
+struct EmailArgs {
+    /// Recipient emails
+    to: Vec<String>,
+    /// Subject of the email
+    subject: String,
+    /// Body (HTML or plain text)
+    body: String,
+}
+
+/// A tool that sends an email using the Resend API.
+#[derive(Deserialize, Serialize)]
+struct EmailSender;
+
+impl Tool for EmailSender {
+    const NAME: &'static str = "send_email";
+
+    type Error = EmailError;
+    type Args = EmailArgs;
+    type Output = String;
+
+    /// The JSON schema / definition for this tool.
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: "send_email".to_string(),
+            description: "Send an email using the Resend API.".to_owned(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "to": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "List of recipient email addresses"
+                    },
+                    "subject": {
+                        "type": "string",
+                        "description": "The subject line for the email"
+                    },
+                    "body": {
+                        "type": "string",
+                        "description": "The body of the email, in HTML or plain text"
+                    }
+                },
+                "required": ["to", "subject", "body"]
+            }),
+        }
+    }
+
+    /// The actual implementation that calls Resend to send the email.
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        // Log the args so we can confirm what we're sending
+        debug!("EmailSender::call() invoked with args: {:?}", args);
+
+        // Check environment variable for Resend
+        match env::var("RESEND_API_KEY") {
+            Ok(key) => {
+                debug!("RESEND_API_KEY is present, length: {}", key.len());
+            }
+            Err(_) => {
+                warn!("RESEND_API_KEY is not set. Make sure it's defined in .env or environment variables.");
+            }
+        }
+
+        // Instantiate the Resend client from the environment variable
+        let resend = Resend::default();
+        let email_options =
+            CreateEmailBaseOptions::new(EMAIL_FROM, &args.to, &args.subject).with_html(&args.body);
+
+        // Attempt to send the email
+        info!("Sending request to Resend...");
+        match resend.emails.send(email_options).await {
+            Ok(_) => {
+                info!("Email sent successfully!");
+                Ok("Email sent successfully!".to_string())
+            }
+            Err(e) => {
+                error!("Failed to send email via Resend: {e}");
+                Err(EmailError(format!("Failed to send email: {e}")))
+            }
+        }
+    }
+}
+
+/// Fired by a schedule; the timestamp is when it was due.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder(DateTime<Utc>);
+
+impl Job for Reminder {
+    const SPEC: JobSpec = JobSpec {
+        worker: "morning-cereal",
+        namespace: "reminder::DailyReminder",
+        concurrency: 1,
+        // Rides out a short DeepSeek or Resend outage; at most
+        // 5 + 10 + 20 + 40 s of waiting keeps it under the two-minute schedule.
+        retries: 4,
+        backoff: Backoff {
+            base: Duration::from_secs(5),
+            max: Duration::from_secs(60),
+            factor: 2,
+            jitter: Jitter::Full,
+        },
+    };
+}
+
+impl From<DateTime<Utc>> for Reminder {
+    fn from(t: DateTime<Utc>) -> Self {
+        Reminder(t)
+    }
+}
+
+/// A little helper to strip out code fences (```json ... ```) from LLM responses,
+/// in case the LLM includes them around valid JSON.
+fn sanitize_json(input: &str) -> String {
+    // Remove leading/trailing whitespace
+    let trimmed = input.trim();
+
+    // Replace any triple-backtick code fences
+    let without_fences = trimmed
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+
+    without_fences.to_string()
+}
+
+async fn send_email_via_agent() -> Result<(), ReminderError> {
+    info!("Preparing to send email via agent...");
+
+    // Create a new DeepSeek client from env
+    let client = providers::deepseek::Client::from_env();
+    debug!("DeepSeek client created.");
+
+    let joke_agent = client
+        .agent("deepseek-chat")
+        .preamble(JOKE_AGENT_PREAMBLE)
+        .max_tokens(300)
+        .build();
+
+    // Generate joke content
+    let json_response = joke_agent
+        .prompt("Create email content with a random joke")
+        .await
+        .map_err(|e| ReminderError::Agent("joke", e))?;
+    info!("Generated joke content: {}", json_response);
+
+    // Sanitize the response in case it comes back wrapped in ```json fences
+    let sanitized = sanitize_json(&json_response);
+
+    // Parse JSON response
+    let email_content: serde_json::Value = serde_json::from_str(&sanitized).map_err(|e| {
+        error!("Failed to parse JSON response: {e}");
+        ReminderError::InvalidJson(e)
+    })?;
+
+    let subject = email_content["subject"]
+        .as_str()
+        .unwrap_or("Daily Laugh 😄");
+    let body = email_content["body"]
+        .as_str()
+        .unwrap_or("Oops, the joke didn't load! But here's a smile anyway: 😊");
+
+    // Create an agent dedicated to sending emails
+    let email_agent = client
+        .agent("deepseek-chat")
+        .preamble("You are an email-sending agent. Use the send_email tool to send messages.")
+        .tool(EmailSender)
+        .max_tokens(1024)
+        .build();
+    debug!("Email agent built successfully.");
+
+    // Construct email prompt with dynamic content
+    let email_prompt = format!(
+        r#"Send an email with:
+        {{
+            "to": ["nicolai.vadim@gmail.com"],
+            "subject": "{}",
+            "body": "<h2>Your Daily Dose of Humor</h2><p>{}</p><p>Have a great day! 🚀</p>"
+        }}"#,
+        subject, body
+    );
+
+    let response = email_agent.prompt(email_prompt).await;
+
+    match response {
+        Ok(r) => {
+            info!("Agent response: {r}");
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to get a response from the email agent: {e}");
+            Err(ReminderError::Agent("email", e))
+        }
+    }
+}
+
+/// Why a reminder wasn't sent.
+#[derive(Error, Debug)]
+pub enum ReminderError {
+    /// The joke agent didn't answer with the JSON its preamble asks for.
+    #[error("The joke agent's reply isn't valid JSON")]
+    InvalidJson(#[source] serde_json::Error),
+    /// DeepSeek, or Resend behind the email tool, failed or timed out.
+    #[error("The {0} agent failed")]
+    Agent(&'static str, #[source] PromptError),
+    /// The idempotency key couldn't be claimed or committed.
+    #[error("Unable to record the reminder")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Timeouts and outages are worth another try; a reply that isn't JSON
+/// means the prompt needs fixing, not another attempt.
+impl JobError for ReminderError {
+    fn is_permanent(&self) -> bool {
+        matches!(self, ReminderError::InvalidJson(_))
+    }
+}
+
+pub async fn say_hello_world(job: Reminder, svc: Data<CronjobData>) -> Result<(), ReminderError> {
+    info!("say_hello_world() job invoked for Reminder: {:?}", job);
+    println!("Hello world from send_reminder()!");
+
+    // Attempt to send email, unless this time's reminder already went out
+    let sent = idempotency::once(&svc.db, Reminder::SPEC.namespace, job.0, || async {
+        send_email_via_agent().await.inspect_err(|e| {
+            error!("Error sending email: {e}");
+        })
+    })
+    .await?;
+    if sent.is_none() {
+        return Ok(());
+    }
+
+    svc.execute(job);
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// src/jobs/retention.rs
+//! Deletes speech clips, and their stored audio, once they're older than the
+//! configured retention period, along with stale job idempotency keys (see
+//! [`idempotency::prune`]). Runs on a cron schedule.
+
+use apalis::prelude::Data;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{sync::Arc, time::Duration};
+use tracing::{info, warn};
+
+use crate::jobs::idempotency;
+use crate::services::audio_storage::AudioStorage;
+
+/// Rows deleted per round trip.
+const BATCH_SIZE: i64 = 500;
+
+/// Fired by the cron stream; the timestamp is when it was scheduled.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionSweep(DateTime<Utc>);
+
+impl From<DateTime<Utc>> for RetentionSweep {
+    fn from(t: DateTime<Utc>) -> Self {
+        RetentionSweep(t)
+    }
+}
+
+#[derive(Clone)]
+pub struct AudioRetention {
+    pub db: PgPool,
+    pub storage: Arc<dyn AudioStorage>,
+    /// Clips are kept forever when unset.
+    pub max_age: Option<Duration>,
+}
+
+impl AudioRetention {
+    /// Deletes expired clips in batches. A clip whose audio can't be deleted
+    /// is kept for the next sweep; returns how many clips were removed.
+    pub async fn sweep(&self) -> Result<u64, sqlx::Error> {
+        let Some(max_age) = self.max_age else {
+            return Ok(0);
+        };
+        let mut removed = 0;
+        let mut after = 0;
+        loop {
+            let expired: Vec<(i32, Option<String>)> = sqlx::query_as(
+                r#"
+                SELECT id, storage_key FROM speech_clips
+                WHERE created_at < NOW() - make_interval(secs => $1) AND id > $2
+                ORDER BY id
+                LIMIT $3
+                "#,
+            )
+            .bind(max_age.as_secs_f64())
+            .bind(after)
+            .bind(BATCH_SIZE)
+            .fetch_all(&self.db)
+            .await?;
+            let Some(&(last, _)) = expired.last() else {
+                break;
+            };
+            after = last;
+
+            let mut ids = Vec::with_capacity(expired.len());
+            for (id, key) in expired {
+                if let Some(key) = key {
+                    if let Err(e) = self.storage.delete(&key).await {
+                        warn!("Keeping clip {id}: unable to delete {key}: {e:#}");
+                        continue;
+                    }
+                }
+                ids.push(id);
+            }
+            removed += sqlx::query("DELETE FROM speech_clips WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(&self.db)
+                .await?
+                .rows_affected();
+        }
+        Ok(removed)
+    }
+}
+
+pub async fn run_retention_sweep(
+    job: RetentionSweep,
+    retention: Data<AudioRetention>,
+) -> Result<(), sqlx::Error> {
+    let removed = retention.sweep().await?;
+    let pruned = idempotency::prune(&retention.db).await?;
+    info!(
+        "Retention sweep scheduled at {} removed {removed} clip(s) and {pruned} idempotency key(s)",
+        job.0
+    );
+    Ok(())
+}
@@ -0,0 +1,195 @@
+// src/jobs/schedules.rs
+//! Cron schedules kept in the `job_schedules` table rather than in the code.
+//! The [`Scheduler`] rereads the table every few seconds and pushes a job
+//! onto the matching queue whenever a schedule comes due, so schedules
+//! created, paused or deleted through `/api/schedules` apply without a
+//! redeploy.
+//!
+//! Each run is claimed by moving `next_run_at` forward with a
+//! compare-and-set, so instances sharing the database never fire it twice.
+
+use apalis::prelude::Storage;
+use apalis_cron::Schedule;
+use apalis_sql::postgres::PostgresStorage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{fmt, future::Future, str::FromStr, time::Duration};
+use tracing::{error, info, warn};
+
+use crate::jobs::{briefing::DailyBriefing, registry, reminder::Reminder};
+
+/// Longest the scheduler sleeps before rereading the table.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What a schedule pushes when it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// The reminder email, see [`crate::jobs::reminder`].
+    Reminder,
+    /// Every user's daily briefing, see [`crate::jobs::briefing`].
+    Briefing,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Reminder => "reminder",
+            JobKind::Briefing => "briefing",
+        }
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reminder" => Ok(JobKind::Reminder),
+            "briefing" => Ok(JobKind::Briefing),
+            other => Err(format!("Unknown job kind: {other}")),
+        }
+    }
+}
+
+/// Parses a cron expression with a leading seconds field, such as
+/// `0 */2 * * * *` (every two minutes).
+pub fn parse_cron(expr: &str) -> Result<Schedule, String> {
+    Schedule::from_str(expr).map_err(|e| format!("Invalid cron expression {expr:?}: {e}"))
+}
+
+/// `(id, name, kind, cron, next_run_at)` of an active schedule.
+type ScheduleRow = (i32, String, String, String, Option<DateTime<Utc>>);
+
+pub struct Scheduler {
+    db: PgPool,
+    reminders: PostgresStorage<Reminder>,
+    briefings: PostgresStorage<DailyBriefing>,
+}
+
+impl Scheduler {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            reminders: registry::storage(&db),
+            briefings: registry::storage(&db),
+            db,
+        }
+    }
+
+    /// Fires schedules as they come due until `stop` resolves. Sleeps until
+    /// the next run but never longer than [`POLL_INTERVAL`], so edits are
+    /// noticed; `stop` is only checked while sleeping, never mid-tick.
+    pub async fn run(mut self, stop: impl Future<Output = ()>) {
+        info!("Scheduler started; polling job_schedules every {POLL_INTERVAL:?}.");
+        tokio::pin!(stop);
+        loop {
+            let wait = match self.tick(Utc::now()).await {
+                Ok(Some(next)) => (next - Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+                    .min(POLL_INTERVAL),
+                Ok(None) => POLL_INTERVAL,
+                Err(e) => {
+                    error!("Unable to read job schedules: {e}");
+                    POLL_INTERVAL
+                }
+            };
+            tokio::select! {
+                () = tokio::time::sleep(wait) => {}
+                () = &mut stop => {
+                    info!("Scheduler stopped.");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Fires every active schedule due at `now` and returns the earliest
+    /// upcoming run. Runs missed while nothing was polling (downtime, a
+    /// pause) fire once, not once per missed slot.
+    pub async fn tick(&mut self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let schedules: Vec<ScheduleRow> = sqlx::query_as(
+            "SELECT id, name, kind, cron, next_run_at FROM job_schedules WHERE NOT paused",
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut earliest: Option<DateTime<Utc>> = None;
+        for (id, name, kind, cron, next_run_at) in schedules {
+            let (kind, schedule) = match (kind.parse::<JobKind>(), parse_cron(&cron)) {
+                (Ok(kind), Ok(schedule)) => (kind, schedule),
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!("Skipping schedule {name}: {e}");
+                    continue;
+                }
+            };
+            let upcoming = schedule.after(&now).next();
+
+            let next = match next_run_at {
+                Some(due) if due <= now => {
+                    if self.claim(id, Some(due), upcoming).await? {
+                        self.fire(&name, kind, due).await;
+                    }
+                    upcoming
+                }
+                Some(due) => Some(due),
+                // New, edited or just resumed.
+                None => {
+                    self.claim(id, None, upcoming).await?;
+                    upcoming
+                }
+            };
+            earliest = match (earliest, next) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+        Ok(earliest)
+    }
+
+    /// Moves a schedule from `due` to `next`; false when another instance,
+    /// or an API call, got there first.
+    async fn claim(
+        &self,
+        id: i32,
+        due: Option<DateTime<Utc>>,
+        next: Option<DateTime<Utc>>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE job_schedules
+            SET next_run_at = $3, last_run_at = COALESCE($2, last_run_at)
+            WHERE id = $1 AND NOT paused AND next_run_at IS NOT DISTINCT FROM $2
+            "#,
+        )
+        .bind(id)
+        .bind(due)
+        .bind(next)
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn fire(&mut self, name: &str, kind: JobKind, due: DateTime<Utc>) {
+        let pushed = match kind {
+            JobKind::Reminder => self.reminders.push(Reminder::from(due)).await.map(drop),
+            JobKind::Briefing => self
+                .briefings
+                .push(DailyBriefing::from(due))
+                .await
+                .map(drop),
+        };
+        match pushed {
+            Ok(()) => info!("Schedule {name} queued a {kind} job for {due}."),
+            Err(e) => error!("Schedule {name} couldn't queue its {kind} job for {due}: {e}"),
+        }
+    }
+}
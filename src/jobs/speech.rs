@@ -0,0 +1,241 @@
+// src/jobs/speech.rs
+//! Background speech generation. `POST /api/speech` records a `speech_jobs`
+//! row and queues a [`SpeechJob`]; the worker chunks, synthesizes and joins
+//! the audio into [`AudioStorage`](crate::services::audio_storage::AudioStorage),
+//! keeping the row's status and chunk progress up to date.
+
+use apalis::prelude::{Data, Storage};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{io, pin::pin};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+use crate::jobs::registry::{Job, JobError, JobSpec};
+use crate::services::speech_pipeline::{
+    chunk_input, concat_audio, synthesize_stream, ChunkError, RetryPolicy, DEFAULT_CONCURRENCY,
+};
+use crate::services::tts_service::{AudioFormat, SpeechOptions};
+use crate::state::AppState;
+use crate::utils::{backoff::Backoff, mp3_info::inspect_mp3};
+
+/// Queue payload; everything else lives on the `speech_jobs` row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechJob {
+    pub job_id: i32,
+}
+
+/// No retries: the pipeline already retries each chunk.
+impl Job for SpeechJob {
+    const SPEC: JobSpec = JobSpec {
+        worker: "speech-synthesizer",
+        namespace: "speech::SpeechJob",
+        concurrency: 2,
+        retries: 0,
+        backoff: Backoff::DEFAULT,
+    };
+}
+
+#[derive(Debug, Error)]
+pub enum SpeechJobError {
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+    #[error(transparent)]
+    Tts(#[from] ChunkError),
+    #[error("Unable to join audio chunks: {0}")]
+    Concat(#[from] io::Error),
+    #[error("Invalid job options: {0}")]
+    Options(String),
+    #[error("Unable to store audio: {0:#}")]
+    Storage(anyhow::Error),
+    #[error("Unable to queue job: {0}")]
+    Queue(String),
+}
+
+impl JobError for SpeechJobError {
+    fn is_permanent(&self) -> bool {
+        match self {
+            SpeechJobError::Options(_) => true,
+            SpeechJobError::Tts(e) => !e.source.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+/// Queues the job for an inserted `speech_jobs` row. If that fails, the row is
+/// marked failed so it doesn't sit in `queued` forever.
+pub async fn enqueue(state: &AppState, job_id: i32) -> Result<(), SpeechJobError> {
+    if let Err(e) = push(state, job_id).await {
+        error!("Unable to queue speech job {job_id}: {e}");
+        sqlx::query(
+            "UPDATE speech_jobs SET status = 'failed', error = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(job_id)
+        .bind(e.to_string())
+        .execute(&state.db)
+        .await?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Queues the job for a `speech_jobs` row and leaves the row alone either
+/// way; for callers that retry with [`is_queued`] instead.
+pub async fn push(state: &AppState, job_id: i32) -> Result<(), SpeechJobError> {
+    let mut queue = state.speech_jobs.clone();
+    queue
+        .push(SpeechJob { job_id })
+        .await
+        .map_err(|e| SpeechJobError::Queue(e.to_string()))?;
+    Ok(())
+}
+
+/// Whether apalis has a job for the `speech_jobs` row, in any state.
+pub async fn is_queued(db: &PgPool, job_id: i32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM apalis.jobs
+            WHERE job_type = $1 AND (job->>'job_id')::int = $2
+        )
+        "#,
+    )
+    .bind(SpeechJob::SPEC.namespace)
+    .bind(job_id)
+    .fetch_one(db)
+    .await
+}
+
+/// Worker entry point. Failures are recorded on the row before being handed
+/// back to apalis.
+pub async fn run_speech_job(job: SpeechJob, state: Data<AppState>) -> Result<(), SpeechJobError> {
+    info!("Speech job {} started", job.job_id);
+
+    if let Err(e) = generate(&state, job.job_id).await {
+        error!("Speech job {} failed: {e}", job.job_id);
+        sqlx::query(
+            "UPDATE speech_jobs SET status = 'failed', error = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(job.job_id)
+        .bind(e.to_string())
+        .execute(&state.db)
+        .await?;
+        return Err(e);
+    }
+
+    info!("Speech job {} done", job.job_id);
+    Ok(())
+}
+
+async fn generate(state: &AppState, job_id: i32) -> Result<(), SpeechJobError> {
+    let (user_id, input, voice, model, format, speed): (
+        i32,
+        String,
+        String,
+        Option<String>,
+        String,
+        f32,
+    ) = sqlx::query_as(
+        "SELECT user_id, input, voice, model, response_format, speed FROM speech_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_one(&state.db)
+    .await?;
+    let options = SpeechOptions {
+        voice,
+        model: model
+            .map(|m| m.parse())
+            .transpose()
+            .map_err(SpeechJobError::Options)?,
+        format: format.parse().map_err(SpeechJobError::Options)?,
+        speed,
+    };
+
+    let chunks = chunk_input(&input, &state.normalizer, state.chunk_limit);
+    let total = chunks.len();
+    sqlx::query(
+        r#"
+        UPDATE speech_jobs
+        SET status = 'running', chunks_total = $2, chunks_done = 0, error = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(job_id)
+    .bind(total as i32)
+    .execute(&state.db)
+    .await?;
+
+    // Chunks arrive in order, so the count of received chunks is the progress.
+    let mut done = 0;
+    let mut audio_chunks = Vec::with_capacity(total);
+    let mut audio = pin!(synthesize_stream(
+        state.tts.clone(),
+        chunks,
+        options.clone(),
+        DEFAULT_CONCURRENCY,
+        RetryPolicy::default(),
+    ));
+    while let Some(chunk) = audio.next().await {
+        audio_chunks.extend(chunk?.into_parts());
+        done += 1;
+        sqlx::query("UPDATE speech_jobs SET chunks_done = $2, updated_at = NOW() WHERE id = $1")
+            .bind(job_id)
+            .bind(done)
+            .execute(&state.db)
+            .await?;
+    }
+
+    let audio = concat_audio(options.format, &audio_chunks)?;
+    let duration_ms = match options.format {
+        AudioFormat::Mp3 => match inspect_mp3(&audio) {
+            Ok(info) => {
+                if info.is_corrupt() {
+                    warn!("Speech job {job_id}: corrupt MP3 frames {:?}", info.corrupt);
+                }
+                Some(info.duration.as_millis() as i32)
+            }
+            Err(e) => {
+                warn!("Speech job {job_id}: unable to measure the audio: {e}");
+                None
+            }
+        },
+        _ => None,
+    };
+    let key = format!("speech/{user_id}/{job_id}.{}", options.format.extension());
+    let size_bytes = audio.len() as i64;
+    state
+        .audio_storage
+        .put(&key, audio, options.format.content_type())
+        .await
+        .map_err(SpeechJobError::Storage)?;
+
+    let mut tx = state.db.begin().await?;
+    let (clip_id,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO speech_clips
+            (user_id, input, voice, response_format, storage_key, duration_ms, size_bytes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(&input)
+    .bind(&options.voice)
+    .bind(options.format.as_str())
+    .bind(&key)
+    .bind(duration_ms)
+    .bind(size_bytes)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE speech_jobs SET status = 'done', clip_id = $2, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(clip_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
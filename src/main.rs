@@ -1,310 +1,306 @@
-use anyhow::Result as AnyResult;
-use apalis::layers::retry::RetryPolicy;
-use apalis::prelude::*;
-use apalis_cron::CronStream;
-use apalis_cron::Schedule;
 use apalis_sql::postgres::PostgresStorage;
-use apalis_sql::Config;
-use chrono::{DateTime, Utc};
+use axum_extra::extract::cookie::Key;
 use dotenv::dotenv;
-use resend_rs::types::CreateEmailBaseOptions;
-use resend_rs::Resend;
-use rig::{
-    completion::{Prompt, ToolDefinition},
-    providers,
-    tool::Tool,
-};
-use serde::{Deserialize, Serialize};
-use serde_json;
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use shuttle_openai::async_openai::{config::OpenAIConfig, Client};
+use shuttle_runtime::{CustomError, SecretStore};
 use std::env;
-use std::str::FromStr;
-use thiserror::Error;
-use tracing::{debug, error, info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+mod endpoints;
+mod jobs;
+mod services;
+mod state;
+mod utils;
+
+use jobs::registry::{self, InFlight};
+use jobs::schedules::Scheduler;
+use services::audio_storage::{AudioStorage, LocalAudioStorage, S3AudioStorage, UrlSigner};
+use services::local_tts::LocalTts;
+use services::tts_cache::{CacheLimits, CachedTts, FsTtsCache, PgTtsCache, TtsCache};
+use services::tts_service::{OpenAiTts, TtsProvider};
+use state::AppState;
+use utils::chunk_text_unicode::ChunkLimit;
+use utils::normalize_text::{Locale, Normalizer};
 
-const JOKE_AGENT_PREAMBLE: &str = r#"
-You are a humorous assistant that generates:
-1. A creative, funny email subject about a random topic
-2. A joke that matches the subject
-Respond ONLY in valid JSON format with:
-{ 
-  "subject": "your funny subject here",
-  "body": "your joke here (keep it work-appropriate)"
-}"#;
-
-#[derive(Clone)]
-struct CronjobData {
-    message: String,
-}
-
-impl CronjobData {
-    fn execute(&self, _item: Reminder) {
-        println!("{} from CronjobData::execute()!", &self.message);
-        info!("CronjobData::execute() finished for item: {:?}", _item);
-    }
-}
-
-/// A custom error for the email-sending tool.
-#[derive(Error, Debug)]
-#[error("Email error: {0}")]
-struct EmailError(String);
-
-/// The arguments our "send_email" tool accepts.
-#[derive(Deserialize, Serialize, Debug)]
-struct EmailArgs {
-    /// Recipient emails
-    to: Vec<String>,
-    /// Subject of the email
-    subject: String,
-    /// Body (HTML or plain text)
-    body: String,
-}
-
-/// A tool that sends an email using the Resend API.
-#[derive(Deserialize, Serialize)]
-struct EmailSender;
-
-impl Tool for EmailSender {
-    const NAME: &'static str = "send_email";
-
-    type Error = EmailError;
-    type Args = EmailArgs;
-    type Output = String;
+#[shuttle_runtime::main]
+async fn shuttle_main(
+    #[shuttle_shared_db::Postgres] conn_string: String,
+    #[shuttle_openai::OpenAI(api_key = "{secrets.OPENAI_API_KEY}")] openai: Client<OpenAIConfig>,
+    #[shuttle_runtime::Secrets] secrets: SecretStore,
+) -> Result<MyService, shuttle_runtime::Error> {
+    dotenv().ok();
 
-    /// The JSON schema / definition for this tool.
-    async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "send_email".to_string(),
-            description: "Send an email using the Resend API.".to_owned(),
-            parameters: serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "to": {
-                        "type": "array",
-                        "items": { "type": "string" },
-                        "description": "List of recipient email addresses"
-                    },
-                    "subject": {
-                        "type": "string",
-                        "description": "The subject line for the email"
-                    },
-                    "body": {
-                        "type": "string",
-                        "description": "The body of the email, in HTML or plain text"
-                    }
-                },
-                "required": ["to", "subject", "body"]
-            }),
+    // TTS_PROVIDER=local swaps OpenAI for silent offline audio (no network, no key needed).
+    let secret = |name: &str| secrets.get(name).or_else(|| env::var(name).ok());
+    let tts: Arc<dyn TtsProvider> = match secret("TTS_PROVIDER").as_deref() {
+        Some("local") => {
+            info!("Using the local TTS provider.");
+            Arc::new(LocalTts::default())
         }
-    }
-
-    /// The actual implementation that calls Resend to send the email.
-    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
-        // Log the args so we can confirm what we're sending
-        debug!("EmailSender::call() invoked with args: {:?}", args);
-
-        // Check environment variable for Resend
-        match env::var("RESEND_API_KEY") {
-            Ok(key) => {
-                debug!("RESEND_API_KEY is present, length: {}", key.len());
-            }
-            Err(_) => {
-                warn!("RESEND_API_KEY is not set. Make sure it's defined in .env or environment variables.");
+        _ => {
+            let api_key = secret("OPENAI_API_KEY")
+                .expect("OPENAI_API_KEY must be set in Secrets.toml or the environment");
+            let mut provider = OpenAiTts::new(api_key);
+            if let Some(base_url) = secret("OPENAI_BASE_URL") {
+                provider = provider.with_base_url(base_url);
             }
+            Arc::new(provider)
         }
-
-        // Instantiate the Resend client from the environment variable
-        let resend = Resend::default();
-        // This `from` must be a verified sender/domain in Resend:
-        let from = "Acme <onboarding@resend.dev>";
-        let email_options =
-            CreateEmailBaseOptions::new(from, &args.to, &args.subject).with_html(&args.body);
-
-        // Attempt to send the email
-        info!("Sending request to Resend...");
-        match resend.emails.send(email_options).await {
-            Ok(_) => {
-                info!("Email sent successfully!");
-                Ok("Email sent successfully!".to_string())
-            }
-            Err(e) => {
-                error!("Failed to send email via Resend: {e}");
-                Err(EmailError(format!("Failed to send email: {e}")))
-            }
+    };
+
+    // AUDIO_STORAGE=local (default) keeps audio under AUDIO_STORAGE_DIR and
+    // signs its own download URLs; AUDIO_STORAGE=s3 uses any S3-compatible store.
+    let audio_storage: Arc<dyn AudioStorage> = match secret("AUDIO_STORAGE").as_deref() {
+        Some("s3") => {
+            let required = |name: &str| {
+                secret(name).unwrap_or_else(|| panic!("{name} must be set for AUDIO_STORAGE=s3"))
+            };
+            info!("Storing audio in S3 bucket {}.", required("S3_BUCKET"));
+            Arc::new(S3AudioStorage::new(
+                required("S3_ENDPOINT"),
+                required("S3_BUCKET"),
+                secret("S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+                required("S3_ACCESS_KEY_ID"),
+                required("S3_SECRET_ACCESS_KEY"),
+            ))
         }
-    }
-}
-
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
-struct Reminder(DateTime<Utc>);
-
-impl From<DateTime<Utc>> for Reminder {
-    fn from(t: DateTime<Utc>) -> Self {
-        Reminder(t)
-    }
-}
-
-/// A little helper to strip out code fences (```json ... ```) from LLM responses,
-/// in case the LLM includes them around valid JSON.
-fn sanitize_json(input: &str) -> String {
-    // Remove leading/trailing whitespace
-    let trimmed = input.trim();
-
-    // Replace any triple-backtick code fences
-    let without_fences = trimmed
-        .trim_start_matches("```json")
-        .trim_start_matches("```")
-        .trim_end_matches("```")
-        .trim();
-
-    without_fences.to_string()
-}
-
-async fn send_email_via_agent() -> AnyResult<()> {
-    info!("Preparing to send email via agent...");
-
-    // Create a new DeepSeek client from env
-    let client = providers::deepseek::Client::from_env();
-    debug!("DeepSeek client created.");
-
-    let joke_agent = client
-        .agent("deepseek-chat")
-        .preamble(JOKE_AGENT_PREAMBLE)
-        .max_tokens(300)
-        .build();
-
-    // Generate joke content
-    let json_response = joke_agent
-        .prompt("Create email content with a random joke")
-        .await?;
-    info!("Generated joke content: {}", json_response);
-
-    // Sanitize the response in case it comes back wrapped in ```json fences
-    let sanitized = sanitize_json(&json_response);
-
-    // Parse JSON response
-    let email_content: serde_json::Value = serde_json::from_str(&sanitized).map_err(|e| {
-        error!("Failed to parse JSON response: {e}");
-        EmailError(format!("Failed to parse JSON response: {e}"))
-    })?;
-
-    let subject = email_content["subject"]
-        .as_str()
-        .unwrap_or("Daily Laugh 😄");
-    let body = email_content["body"]
-        .as_str()
-        .unwrap_or("Oops, the joke didn't load! But here's a smile anyway: 😊");
-
-    // Create an agent dedicated to sending emails
-    let email_agent = client
-        .agent("deepseek-chat")
-        .preamble("You are an email-sending agent. Use the send_email tool to send messages.")
-        .tool(EmailSender)
-        .max_tokens(1024)
-        .build();
-    debug!("Email agent built successfully.");
+        _ => {
+            let dir = secret("AUDIO_STORAGE_DIR").unwrap_or_else(|| "audio".to_string());
+            let signer = match secret("AUDIO_URL_SECRET") {
+                Some(secret) => UrlSigner::new(secret),
+                None => {
+                    warn!("AUDIO_URL_SECRET is not set; audio links won't survive a restart.");
+                    UrlSigner::new(Key::generate().signing())
+                }
+            };
+            info!("Storing audio in {dir}.");
+            Arc::new(
+                LocalAudioStorage::new(dir, signer)
+                    .expect("Unable to create the audio storage directory"),
+            )
+        }
+    };
+
+    // AUDIO_RETENTION_DAYS (30 by default, 0 keeps audio forever).
+    let retention_days = secret("AUDIO_RETENTION_DAYS")
+        .map(|days| {
+            days.parse::<u64>()
+                .expect("AUDIO_RETENTION_DAYS must be a whole number of days")
+        })
+        .unwrap_or(30);
+    let audio_retention =
+        (retention_days > 0).then(|| Duration::from_secs(retention_days * 24 * 60 * 60));
+
+    // SHUTDOWN_TIMEOUT_SECS (30 by default): how long a SIGTERM or ctrl-c
+    // waits for running jobs and requests before giving up on them.
+    let shutdown_timeout = secret("SHUTDOWN_TIMEOUT_SECS")
+        .map(|secs| {
+            secs.parse::<u64>()
+                .expect("SHUTDOWN_TIMEOUT_SECS must be a whole number of seconds")
+        })
+        .map_or(Duration::from_secs(30), Duration::from_secs);
 
-    // Construct email prompt with dynamic content
-    let email_prompt = format!(
-        r#"Send an email with:
-        {{
-            "to": ["nicolai.vadim@gmail.com"],
-            "subject": "{}",
-            "body": "<h2>Your Daily Dose of Humor</h2><p>{}</p><p>Have a great day! 🚀</p>"
-        }}"#,
-        subject, body
-    );
+    // Create connection pool
+    let mut state = AppState::new(conn_string, openai, tts, audio_storage)
+        .await
+        .expect("Failed to connect to the Postgres database");
 
-    let response = email_agent.prompt(email_prompt).await;
+    info!("Database connection pool established successfully.");
 
-    match response {
-        Ok(r) => {
-            info!("Agent response: {r}");
-            Ok(())
+    state.seed().await;
+    info!("App migrations completed successfully.");
+
+    // TTS_CACHE=postgres (default) | fs | off; TTS_CACHE_MAX_MB caps its total size.
+    let cache: Option<Arc<dyn TtsCache>> = match secret("TTS_CACHE").as_deref() {
+        Some("off") => None,
+        Some("fs") => {
+            let dir = secret("TTS_CACHE_DIR").unwrap_or_else(|| "tts-cache".to_string());
+            Some(Arc::new(
+                FsTtsCache::new(dir).expect("Unable to create the TTS cache directory"),
+            ))
         }
-        Err(e) => {
-            error!("Failed to get a response from the email agent: {e}");
-            Err(e.into())
+        _ => Some(Arc::new(PgTtsCache::new(state.db.clone()))),
+    };
+    if let Some(cache) = cache {
+        let mut limits = CacheLimits::default();
+        if let Some(mb) = secret("TTS_CACHE_MAX_MB").and_then(|mb| mb.parse::<u64>().ok()) {
+            limits.max_total_bytes = mb * 1024 * 1024;
         }
+        state.tts = Arc::new(CachedTts::new(
+            state.tts.clone(),
+            cache,
+            limits,
+            state.tts_cache_metrics.clone(),
+        ));
+        info!("TTS cache enabled, up to {} bytes.", limits.max_total_bytes);
     }
-}
-
-async fn say_hello_world(job: Reminder, svc: Data<CronjobData>) {
-    info!("say_hello_world() job invoked for Reminder: {:?}", job);
-    println!("Hello world from send_reminder()!");
 
-    // Attempt to send email
-    if let Err(e) = send_email_via_agent().await {
-        error!("Error sending email: {e}");
-        eprintln!("Error sending email: {e}");
+    // TTS_LOCALE (en-US by default) picks how dates, numbers and phone
+    // numbers are spelled out before synthesis.
+    if let Some(locale) = secret("TTS_LOCALE") {
+        let locale: Locale = locale.parse().expect("TTS_LOCALE must be en-US or en-GB");
+        state.normalizer = Arc::new(Normalizer::new(locale));
+        info!("Normalizing speech input for {locale:?}.");
     }
 
-    svc.execute(job);
-}
-
-#[shuttle_runtime::main]
-async fn shuttle_main(
-    #[shuttle_shared_db::Postgres] conn_string: String,
-) -> Result<MyService, shuttle_runtime::Error> {
-    dotenv().ok();
-
-    // Create connection pool
-    let db = PgPoolOptions::new()
-        .min_connections(5)
-        .max_connections(5)
-        .connect(&conn_string)
-        .await
-        .expect("Failed to connect to the Postgres database");
+    // TTS_MAX_INPUT_BYTES caps each TTS request's input in UTF-8 bytes rather
+    // than OpenAI's 4096 characters, for compatible servers (OPENAI_BASE_URL)
+    // that count that way.
+    if let Some(bytes) = secret("TTS_MAX_INPUT_BYTES") {
+        let bytes = bytes
+            .parse()
+            .expect("TTS_MAX_INPUT_BYTES must be a whole number of bytes");
+        state.chunk_limit = ChunkLimit::Bytes(bytes);
+        info!("Chunking speech input to {bytes} bytes per request.");
+    }
 
-    info!("Database connection pool established successfully.");
+    // NATURAL_DATE_FALLBACK=deepseek asks DeepSeek about reminder dates the
+    // parser doesn't understand (off by default).
+    if secret("NATURAL_DATE_FALLBACK").as_deref() == Some("deepseek") {
+        state.date_agent = true;
+        info!("Unrecognized reminder dates fall back to DeepSeek.");
+    }
 
-    Ok(MyService { db })
+    Ok(MyService {
+        state,
+        audio_retention,
+        shutdown_timeout,
+    })
 }
 
 // Customize this struct with things from `shuttle_main` needed in `bind`.
 struct MyService {
-    db: PgPool,
+    state: AppState,
+    /// How long speech clips are kept; `None` keeps them forever.
+    audio_retention: Option<Duration>,
+    /// How long in-flight jobs and requests get to finish on shutdown.
+    shutdown_timeout: Duration,
+}
+
+/// Resolves on ctrl-c or, on Unix, SIGTERM (what a deploy sends).
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Unable to listen for ctrl-c");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Unable to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
 }
 
 #[shuttle_runtime::async_trait]
 impl shuttle_runtime::Service for MyService {
-    async fn bind(self, _addr: std::net::SocketAddr) -> Result<(), shuttle_runtime::Error> {
-        info!("MyService::bind() called. Setting up storage and cron worker...");
-
-        // set up storage
-        PostgresStorage::setup(&self.db)
+    async fn bind(self, addr: std::net::SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        info!("MyService::bind() called. Setting up storage, workers and HTTP server...");
+
+        // set up storage; the app's migrations share `_sqlx_migrations`, so
+        // apalis mustn't fail on versions it doesn't know about either.
+        let mut migrator = PostgresStorage::migrations();
+        migrator.set_ignore_missing(true);
+        migrator
+            .run(&self.state.db)
             .await
             .expect("Unable to run migrations :(");
         info!("PostgresStorage migrations completed successfully.");
 
-        // You can provide a unique namespace name in `Config::new`
-        let config = Config::new("reminder::DailyReminder");
-        let storage = PostgresStorage::new_with_config(self.db.clone(), config);
-        debug!("PostgresStorage with custom config created.");
-
-        // Create a schedule that runs every 2 minutes (at second 0).
-        let schedule_str = "0 */2 * * * *";
-        info!("Using schedule: {}", schedule_str);
-        let schedule = Schedule::from_str(schedule_str)
-            .expect("Couldn't create the schedule from cron expression!");
-
-        let cron_service_ext = CronjobData {
-            message: "Hello world".to_string(),
+        // Every registered job kind gets its worker; the scheduler pushes the
+        // cron-driven ones from `job_schedules` as they come due. Worker ids
+        // are unique to this boot, so only our own jobs are requeued below.
+        let instance = format!("{:08x}", rand::random::<u32>());
+        let in_flight = InFlight::default();
+        let monitor =
+            registry::register_workers(&self.state, &instance, &in_flight, self.audio_retention);
+        let scheduler = Scheduler::new(self.state.db.clone());
+
+        let router = endpoints::router(self.state.clone());
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(CustomError::new)?;
+        info!("HTTP server listening on {addr}");
+
+        // A shutdown signal, or any one of the HTTP server, the workers and
+        // the scheduler stopping, stops the other two: they take no new
+        // requests or jobs and get `shutdown_timeout` to finish what they have.
+        let timeout = self.shutdown_timeout;
+        let stop = CancellationToken::new();
+        tokio::spawn({
+            let stop = stop.clone();
+            async move {
+                tokio::select! {
+                    () = shutdown_signal() => {
+                        info!("Shutting down; waiting up to {timeout:?} for jobs and requests.");
+                        stop.cancel();
+                    }
+                    () = stop.cancelled() => {}
+                }
+            }
+        });
+        let deadline = || {
+            let stop = stop.clone();
+            async move {
+                stop.cancelled().await;
+                tokio::time::sleep(timeout).await;
+            }
         };
 
-        let persisted_cron = CronStream::new(schedule).pipe_to_storage(storage);
-        debug!("Cron stream setup complete; now building worker.");
+        let http = async {
+            let serve = axum::serve(listener, router)
+                .with_graceful_shutdown(stop.clone().cancelled_owned());
+            let res = tokio::select! {
+                res = serve => res,
+                () = deadline() => {
+                    warn!("Dropping HTTP requests still open after {timeout:?}.");
+                    Ok(())
+                }
+            };
+            stop.cancel();
+            res
+        };
+        let workers = async {
+            let stopped = stop.clone().cancelled_owned();
+            let res = monitor
+                .shutdown_timeout(timeout)
+                .run_with_signal(async {
+                    stopped.await;
+                    Ok(())
+                })
+                .await;
+            stop.cancel();
+            res
+        };
+        let scheduling = async {
+            scheduler.run(stop.clone().cancelled_owned()).await;
+            stop.cancel();
+        };
 
-        // Build worker
-        let worker = WorkerBuilder::new("morning-cereal")
-            .data(cron_service_ext)
-            .retry(RetryPolicy::retries(5))
-            .backend(persisted_cron)
-            .build_fn(say_hello_world);
+        info!("Workers built; running workers and HTTP server now.");
+        let (http, workers, ()) = tokio::join!(http, workers, scheduling);
 
-        info!("Worker built; running worker now.");
-        worker.run().await;
+        // Whatever the workers didn't finish in time goes back on the queue
+        // for the next instance to pick up.
+        match registry::requeue_unfinished(&self.state.db, &instance, &in_flight).await {
+            Ok(0) => info!("All jobs finished before shutdown."),
+            Ok(n) => warn!("Requeued {n} job(s) left unfinished by the shutdown."),
+            Err(e) => error!("Unable to requeue unfinished jobs: {e}"),
+        }
 
+        http.map_err(CustomError::new)?;
+        workers.map_err(CustomError::new)?;
         Ok(())
     }
 }
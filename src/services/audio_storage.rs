@@ -0,0 +1,464 @@
+// src/services/audio_storage.rs
+//! Where generated audio lives once a job is done. Clips are written under a
+//! key such as `speech/1/42.mp3` and handed out as signed, expiring URLs, so
+//! browsers (and email links) can fetch them without an API session.
+
+use anyhow::{anyhow, bail, Result as AnyResult};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use sha2::Sha256;
+use std::{fs, path::PathBuf, time::Duration};
+
+use crate::utils::sigv4::{hmac_sha256, uri_encode, SigV4};
+
+/// How long URLs handed to clients stay valid.
+pub const DEFAULT_URL_TTL: Duration = Duration::from_secs(60 * 60);
+/// Where [`LocalAudioStorage`] serves its files from.
+pub const LOCAL_DOWNLOAD_PATH: &str = "/api/audio";
+
+#[axum::async_trait]
+pub trait AudioStorage: Send + Sync {
+    async fn put(&self, key: &str, audio: Vec<u8>, content_type: &str) -> AnyResult<()>;
+    async fn get(&self, key: &str) -> AnyResult<Option<Vec<u8>>>;
+    /// Deleting a missing object is not an error.
+    async fn delete(&self, key: &str) -> AnyResult<()>;
+    /// A URL anyone can GET `key` from until `ttl` has passed.
+    fn signed_url(&self, key: &str, ttl: Duration) -> AnyResult<String>;
+    /// Checks the query of a URL from [`signed_url`](Self::signed_url), for
+    /// backends whose downloads go through `GET /api/audio/*key`. Backends
+    /// that sign URLs to their own server never see those requests.
+    fn verify_url(&self, _key: &str, _expires: i64, _signature: &str) -> bool {
+        false
+    }
+}
+
+/// Keys are relative paths of `[A-Za-z0-9._-]` segments, so they map safely
+/// onto both file paths and object names.
+pub fn validate_key(key: &str) -> AnyResult<()> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+        });
+    if !valid {
+        bail!("invalid audio key {key:?}");
+    }
+    Ok(())
+}
+
+/// HMAC-SHA256 over `key` and its expiry, for URLs this server answers itself.
+#[derive(Clone)]
+pub struct UrlSigner {
+    secret: Vec<u8>,
+}
+
+impl UrlSigner {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    pub fn sign(&self, key: &str, expires: i64) -> String {
+        hex::encode(hmac_sha256(&self.secret, &Self::message(key, expires)))
+    }
+
+    /// True for an unexpired, untampered signature; compared in constant time.
+    pub fn verify(&self, key: &str, expires: i64, signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(&Self::message(key, expires));
+
+        expires >= Utc::now().timestamp() && mac.verify_slice(&signature).is_ok()
+    }
+
+    fn message(key: &str, expires: i64) -> Vec<u8> {
+        format!("{key}\n{expires}").into_bytes()
+    }
+}
+
+/// One file per key under `dir`, served by `GET /api/audio/*key`.
+pub struct LocalAudioStorage {
+    dir: PathBuf,
+    signer: UrlSigner,
+}
+
+impl LocalAudioStorage {
+    pub fn new(dir: impl Into<PathBuf>, signer: UrlSigner) -> std::io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, signer })
+    }
+
+    fn path(&self, key: &str) -> AnyResult<PathBuf> {
+        validate_key(key)?;
+        Ok(self.dir.join(key))
+    }
+}
+
+#[axum::async_trait]
+impl AudioStorage for LocalAudioStorage {
+    async fn put(&self, key: &str, audio: Vec<u8>, _content_type: &str) -> AnyResult<()> {
+        let path = self.path(key)?;
+        let tmp = path.with_extension("tmp");
+        tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Write then rename, so readers never see a half-written file.
+            fs::write(&tmp, audio)?;
+            fs::rename(&tmp, &path)
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        let audio = tokio::task::spawn_blocking(move || match fs::read(path) {
+            Ok(audio) => Ok(Some(audio)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        })
+        .await??;
+
+        Ok(audio)
+    }
+
+    async fn delete(&self, key: &str) -> AnyResult<()> {
+        let path = self.path(key)?;
+        tokio::task::spawn_blocking(move || match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    fn signed_url(&self, key: &str, ttl: Duration) -> AnyResult<String> {
+        validate_key(key)?;
+        let expires = Utc::now().timestamp() + ttl.as_secs() as i64;
+
+        Ok(format!(
+            "{LOCAL_DOWNLOAD_PATH}/{key}?expires={expires}&signature={}",
+            self.signer.sign(key, expires)
+        ))
+    }
+
+    fn verify_url(&self, key: &str, expires: i64, signature: &str) -> bool {
+        self.signer.verify(key, expires, signature)
+    }
+}
+
+/// Objects in a bucket of an S3-compatible store (AWS S3, MinIO, R2, ...),
+/// addressed path-style: `{endpoint}/{bucket}/{key}`.
+pub struct S3AudioStorage {
+    client: Client,
+    /// `scheme://host[:port]`, without a trailing slash.
+    endpoint: String,
+    bucket: String,
+    signer: SigV4,
+}
+
+impl S3AudioStorage {
+    pub fn new(
+        endpoint: impl Into<String>,
+        bucket: impl Into<String>,
+        region: impl Into<String>,
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            bucket: bucket.into(),
+            signer: SigV4 {
+                access_key_id: access_key_id.into(),
+                secret_access_key: secret_access_key.into(),
+                region: region.into(),
+                service: "s3".to_string(),
+            },
+        }
+    }
+
+    fn host(&self) -> &str {
+        self.endpoint
+            .split_once("://")
+            .map_or(self.endpoint.as_str(), |(_, host)| host)
+    }
+
+    fn path(&self, key: &str) -> AnyResult<String> {
+        validate_key(key)?;
+        Ok(format!(
+            "/{}/{}",
+            uri_encode(&self.bucket, false),
+            uri_encode(key, true)
+        ))
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> AnyResult<reqwest::Response> {
+        let path = self.path(key)?;
+        let headers =
+            self.signer
+                .sign_headers(method.as_str(), self.host(), &path, &body, Utc::now());
+
+        let mut request = self
+            .client
+            .request(method, format!("{}{path}", self.endpoint))
+            .body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(content_type) = content_type {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+
+        Ok(request.send().await?)
+    }
+}
+
+#[axum::async_trait]
+impl AudioStorage for S3AudioStorage {
+    async fn put(&self, key: &str, audio: Vec<u8>, content_type: &str) -> AnyResult<()> {
+        let res = self
+            .send(reqwest::Method::PUT, key, audio, Some(content_type))
+            .await?;
+        if !res.status().is_success() {
+            return Err(s3_error("PUT", key, res).await);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let res = self
+            .send(reqwest::Method::GET, key, Vec::new(), None)
+            .await?;
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(res.bytes().await?.to_vec())),
+            _ => Err(s3_error("GET", key, res).await),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> AnyResult<()> {
+        let res = self
+            .send(reqwest::Method::DELETE, key, Vec::new(), None)
+            .await?;
+        if !res.status().is_success() && res.status() != StatusCode::NOT_FOUND {
+            return Err(s3_error("DELETE", key, res).await);
+        }
+        Ok(())
+    }
+
+    fn signed_url(&self, key: &str, ttl: Duration) -> AnyResult<String> {
+        let path = self.path(key)?;
+        // SigV4 presigned URLs are capped at seven days.
+        let expires = ttl.as_secs().clamp(1, 7 * 24 * 60 * 60);
+        let query = self
+            .signer
+            .presign_query("GET", self.host(), &path, expires, Utc::now());
+
+        Ok(format!("{}{path}?{query}", self.endpoint))
+    }
+}
+
+async fn s3_error(method: &str, key: &str, res: reqwest::Response) -> anyhow::Error {
+    let status = res.status();
+    let body = res.text().await.unwrap_or_default();
+    anyhow!("S3 {method} {key} failed with {status}: {body}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, Method, Uri},
+        Router,
+    };
+    use chrono::NaiveDateTime;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    fn signer() -> UrlSigner {
+        UrlSigner::new("test secret")
+    }
+
+    #[test]
+    fn signatures_verify_until_they_expire() {
+        let expires = Utc::now().timestamp() + 60;
+        let signature = signer().sign("speech/1/42.mp3", expires);
+
+        assert!(signer().verify("speech/1/42.mp3", expires, &signature));
+        assert!(!UrlSigner::new("other secret").verify("speech/1/42.mp3", expires, &signature));
+    }
+
+    #[test]
+    fn tampered_urls_dont_verify() {
+        let expires = Utc::now().timestamp() + 60;
+        let signature = signer().sign("speech/1/42.mp3", expires);
+
+        assert!(!signer().verify("speech/1/43.mp3", expires, &signature));
+        assert!(!signer().verify("speech/1/42.mp3", expires + 1, &signature));
+        assert!(!signer().verify("speech/1/42.mp3", expires, &signature[2..]));
+        assert!(!signer().verify("speech/1/42.mp3", expires, "not hex"));
+    }
+
+    #[test]
+    fn expired_signatures_dont_verify() {
+        let expires = Utc::now().timestamp() - 1;
+        let signature = signer().sign("speech/1/42.mp3", expires);
+
+        assert!(!signer().verify("speech/1/42.mp3", expires, &signature));
+    }
+
+    #[test]
+    fn keys_are_relative_paths() {
+        assert!(validate_key("speech/1/42.mp3").is_ok());
+        for key in [
+            "",
+            "/speech/1.mp3",
+            "speech//1.mp3",
+            "../1.mp3",
+            "a/./b",
+            "a b.mp3",
+        ] {
+            assert!(validate_key(key).is_err(), "{key:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn local_storage_puts_gets_and_deletes() {
+        let dir = std::env::temp_dir().join(format!("crm-audio-storage-{}", std::process::id()));
+        let storage = LocalAudioStorage::new(&dir, signer()).unwrap();
+
+        storage
+            .put("speech/1/42.mp3", b"audio".to_vec(), "audio/mpeg")
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get("speech/1/42.mp3").await.unwrap(),
+            Some(b"audio".to_vec())
+        );
+        assert!(!dir.join("speech/1/42.tmp").exists());
+
+        storage.delete("speech/1/42.mp3").await.unwrap();
+        assert_eq!(storage.get("speech/1/42.mp3").await.unwrap(), None);
+        // Deleting what's already gone is fine.
+        storage.delete("speech/1/42.mp3").await.unwrap();
+        assert!(storage.get("../42.mp3").await.is_err());
+
+        let url = storage
+            .signed_url("speech/1/42.mp3", Duration::from_secs(60))
+            .unwrap();
+        let query = url
+            .strip_prefix("/api/audio/speech/1/42.mp3?expires=")
+            .unwrap();
+        let (expires, signature) = query.split_once("&signature=").unwrap();
+        assert!(storage.verify_url("speech/1/42.mp3", expires.parse().unwrap(), signature));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Credentials the S3 stand-in accepts.
+    fn s3_signer() -> SigV4 {
+        SigV4 {
+            access_key_id: "minio".to_string(),
+            secret_access_key: "minio-secret".to_string(),
+            region: "us-east-1".to_string(),
+            service: "s3".to_string(),
+        }
+    }
+
+    type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// An in-memory S3 stand-in that, like MinIO, refuses requests whose
+    /// signature doesn't match what it computes itself.
+    async fn s3_stand_in(
+        State(objects): State<Objects>,
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> (StatusCode, Vec<u8>) {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let date = header("x-amz-date")
+            .and_then(|date| NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%SZ").ok());
+        let (Some(host), Some(date)) = (header("host"), date) else {
+            return (StatusCode::BAD_REQUEST, Vec::new());
+        };
+        let [_, _, (_, expected)] =
+            s3_signer().sign_headers(method.as_str(), host, uri.path(), &body, date.and_utc());
+        if header("authorization") != Some(expected.as_str()) {
+            return (StatusCode::FORBIDDEN, Vec::new());
+        }
+
+        let mut objects = objects.lock().unwrap();
+        let key = uri.path().to_string();
+        match method {
+            Method::PUT => {
+                objects.insert(key, body.to_vec());
+                (StatusCode::OK, Vec::new())
+            }
+            Method::GET => match objects.get(&key) {
+                Some(object) => (StatusCode::OK, object.clone()),
+                None => (StatusCode::NOT_FOUND, Vec::new()),
+            },
+            Method::DELETE => {
+                objects.remove(&key);
+                (StatusCode::NO_CONTENT, Vec::new())
+            }
+            _ => (StatusCode::METHOD_NOT_ALLOWED, Vec::new()),
+        }
+    }
+
+    #[tokio::test]
+    async fn s3_storage_round_trips_through_a_signature_checking_store() {
+        let objects = Objects::default();
+        let app = Router::new()
+            .fallback(s3_stand_in)
+            .with_state(objects.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let storage = S3AudioStorage::new(&endpoint, "audio", "us-east-1", "minio", "minio-secret");
+        storage
+            .put("speech/1/42.mp3", b"audio".to_vec(), "audio/mpeg")
+            .await
+            .unwrap();
+        assert!(objects
+            .lock()
+            .unwrap()
+            .contains_key("/audio/speech/1/42.mp3"));
+        assert_eq!(
+            storage.get("speech/1/42.mp3").await.unwrap(),
+            Some(b"audio".to_vec())
+        );
+        storage.delete("speech/1/42.mp3").await.unwrap();
+        assert_eq!(storage.get("speech/1/42.mp3").await.unwrap(), None);
+
+        let wrong_secret = S3AudioStorage::new(&endpoint, "audio", "us-east-1", "minio", "guess");
+        let e = wrong_secret.get("speech/1/42.mp3").await.unwrap_err();
+        assert!(e.to_string().contains("403"), "{e}");
+    }
+}
@@ -0,0 +1,60 @@
+// src/services/date_agent.rs
+//! DeepSeek fallback for dates [`natural_date::parse`] can't read. The agent
+//! only turns the phrase into a local date and time; resolving it in the
+//! user's timezone stays deterministic.
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use rig::{
+    completion::{Prompt, PromptError},
+    providers,
+};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::utils::natural_date;
+
+const DATE_AGENT_PREAMBLE: &str = r#"
+You turn date and time expressions into a local date and time.
+Reply with ONLY the date and time as YYYY-MM-DDTHH:MM, using 09:00 when no
+time is given, or with UNKNOWN if the text doesn't name a date."#;
+
+/// Format the agent answers in.
+const REPLY_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+#[derive(Debug, Error)]
+pub enum DateAgentError {
+    #[error("The date agent failed")]
+    Agent(#[source] PromptError),
+    #[error("The date agent couldn't read {0:?}")]
+    Unrecognized(String),
+}
+
+/// Asks DeepSeek when `input` is, as of `now` in `tz`.
+pub async fn resolve<Tz: TimeZone>(
+    input: &str,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Result<DateTime<Utc>, DateAgentError> {
+    let agent = providers::deepseek::Client::from_env()
+        .agent("deepseek-chat")
+        .preamble(DATE_AGENT_PREAMBLE)
+        .temperature(0.0)
+        .max_tokens(20)
+        .build();
+
+    let local_now = now.with_timezone(tz).naive_local();
+    let reply = agent
+        .prompt(format!(
+            "It is now {}. When is: {}",
+            local_now.format("%A %Y-%m-%d %H:%M"),
+            input.trim()
+        ))
+        .await
+        .map_err(DateAgentError::Agent)?;
+    debug!("Date agent read {input:?} as {reply:?}");
+
+    NaiveDateTime::parse_from_str(reply.trim(), REPLY_FORMAT)
+        .ok()
+        .and_then(|local| natural_date::resolve_local(tz, local))
+        .ok_or_else(|| DateAgentError::Unrecognized(input.trim().to_string()))
+}
@@ -0,0 +1,156 @@
+// src/services/local_tts.rs
+//! An offline [`TtsProvider`] that never touches the network. It turns text
+//! into silence whose length grows with the input, so the chunk → synthesize
+//! → concatenate pipeline can be exercised in tests and local development.
+//! MP3 is built from valid frames; WAV and PCM from 24 kHz 16-bit mono
+//! samples, like OpenAI's.
+
+use crate::services::tts_service::{AudioFormat, SpeechOptions, TtsError, TtsProvider, PCM_FORMAT};
+use crate::utils::concat_wav::wav_from_samples;
+use crate::utils::mp3_frame::{self, ChannelMode, FrameHeader, Layer, MpegVersion};
+
+/// MPEG-2 Layer III, 24 kHz mono at 32 kbps: 96-byte frames of 24 ms each.
+const HEADER: FrameHeader = FrameHeader {
+    version: MpegVersion::V2,
+    layer: Layer::L3,
+    crc: false,
+    bitrate_kbps: 32,
+    sample_rate: 24_000,
+    padding: false,
+    channel_mode: ChannelMode::Mono,
+};
+const FRAME_MS: u32 = 24;
+
+#[derive(Debug, Clone)]
+pub struct LocalTts {
+    /// How much audio each input character is worth.
+    pub ms_per_char: u32,
+}
+
+impl Default for LocalTts {
+    fn default() -> Self {
+        Self { ms_per_char: 60 }
+    }
+}
+
+impl LocalTts {
+    /// Renders `duration_ms` of MP3; always at least one frame.
+    pub fn render(&self, duration_ms: u32) -> Vec<u8> {
+        let count = duration_ms.div_ceil(FRAME_MS).max(1) as usize;
+
+        silent_frame().repeat(count)
+    }
+
+    /// Renders `duration_ms` of raw 16-bit little-endian samples.
+    pub fn render_pcm(&self, duration_ms: u32) -> Vec<u8> {
+        let count = u64::from(duration_ms) * u64::from(PCM_FORMAT.sample_rate) / 1000;
+
+        vec![0; count as usize * 2]
+    }
+}
+
+#[axum::async_trait]
+impl TtsProvider for LocalTts {
+    fn model(&self) -> &str {
+        "local-silence"
+    }
+
+    /// There's only one local model; a requested one is ignored.
+    fn model_for<'a>(&'a self, _options: &SpeechOptions) -> &'a str {
+        self.model()
+    }
+
+    async fn synthesize(&self, input: &str, options: &SpeechOptions) -> Result<Vec<u8>, TtsError> {
+        let chars = input.chars().count() as u32;
+        let duration_ms = (f64::from(chars.saturating_mul(self.ms_per_char))
+            / f64::from(options.speed.max(0.25))) as u32;
+
+        match options.format {
+            AudioFormat::Mp3 => Ok(self.render(duration_ms)),
+            AudioFormat::Pcm => Ok(self.render_pcm(duration_ms)),
+            AudioFormat::Wav => Ok(wav_from_samples(&PCM_FORMAT, &self.render_pcm(duration_ms))),
+            other => Err(TtsError::UnsupportedFormat(other)),
+        }
+    }
+}
+
+/// A frame whose side info is all zeros: no Huffman data, so it decodes to silence.
+pub fn silent_frame() -> Vec<u8> {
+    mp3_frame::silent_frame(&HEADER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::speech_pipeline::{
+        chunk_input, concat_audio, synthesize_stream, RetryPolicy, DEFAULT_CHUNK_LIMIT,
+    };
+    use crate::utils::{concat_wav::parse_wav, mp3_info::inspect_mp3, normalize_text::Normalizer};
+    use futures::TryStreamExt;
+    use std::{sync::Arc, time::Duration};
+
+    const SCRIPT: &str = "Hello there. [pause 500ms] General Kenobi.";
+
+    /// Runs `SCRIPT` through the whole pipeline, the way a speech job does.
+    async fn speak(format: AudioFormat) -> Vec<u8> {
+        let chunks = chunk_input(SCRIPT, &Normalizer::default(), DEFAULT_CHUNK_LIMIT);
+        assert_eq!(chunks.len(), 2);
+        let options = SpeechOptions {
+            format,
+            ..SpeechOptions::default()
+        };
+        let spoken: Vec<_> = synthesize_stream(
+            Arc::new(LocalTts::default()),
+            chunks,
+            options,
+            2,
+            RetryPolicy::default(),
+        )
+        .try_collect()
+        .await
+        .unwrap();
+        let parts: Vec<Vec<u8>> = spoken.into_iter().flat_map(|c| c.into_parts()).collect();
+        assert_eq!(parts.len(), 3, "speech, silence, speech");
+
+        concat_audio(format, &parts).unwrap()
+    }
+
+    #[tokio::test]
+    async fn mp3_runs_through_the_pipeline() {
+        let info = inspect_mp3(&speak(AudioFormat::Mp3).await).unwrap();
+
+        assert!(!info.is_corrupt());
+        assert_eq!((info.sample_rate, info.channels), (24_000, 1));
+        // 12 and 15 characters at 60 ms each, plus the pause, each part
+        // rounded up to whole 24 ms frames.
+        let expected = Duration::from_millis(720 + 500 + 900);
+        assert!(info.duration >= expected, "{:?}", info.duration);
+        assert!(info.duration < expected + Duration::from_millis(3 * 24));
+    }
+
+    #[tokio::test]
+    async fn wav_runs_through_the_pipeline() {
+        let audio = speak(AudioFormat::Wav).await;
+        let wav = parse_wav(&audio).unwrap();
+
+        assert_eq!(wav.format, PCM_FORMAT);
+        // 2.12 s of 16-bit samples at 24 kHz.
+        assert_eq!(wav.data.len(), 2 * 24_000 * 2120 / 1000);
+    }
+
+    #[tokio::test]
+    async fn other_formats_are_unsupported() {
+        let e = LocalTts::default()
+            .synthesize(
+                "Hi.",
+                &SpeechOptions {
+                    format: AudioFormat::Opus,
+                    ..SpeechOptions::default()
+                },
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(e, TtsError::UnsupportedFormat(AudioFormat::Opus)));
+    }
+}
@@ -1 +1,6 @@
+pub mod audio_storage;
+pub mod date_agent;
+pub mod local_tts;
+pub mod speech_pipeline;
+pub mod tts_cache;
 pub mod tts_service;
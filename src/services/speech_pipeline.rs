@@ -0,0 +1,444 @@
+// src/services/speech_pipeline.rs
+//! Synthesizes many text chunks at once while keeping their audio in order,
+//! with the silence asked for by pause markup in between.
+
+use futures::{stream, Stream, StreamExt};
+use std::{io, sync::Arc, time::Duration};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::services::tts_service::{AudioFormat, SpeechOptions, TtsError, TtsProvider, PCM_FORMAT};
+use crate::utils::{
+    backoff::Backoff,
+    chunk_text_unicode::{chunk_text_sentences, ChunkLimit},
+    concat_flac::concat_flac,
+    concat_mp3::concat_mp3_bytes,
+    concat_ogg::concat_ogg_opus,
+    concat_wav::{concat_wav, parse_wav, wav_from_samples},
+    mp3_frame,
+    normalize_text::Normalizer,
+    speech_markup::{parse_markup, Segment},
+};
+
+/// How many chunks are in flight against the TTS provider at once.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+/// The OpenAI speech endpoint rejects inputs longer than this many characters.
+pub const MAX_CHUNK_CHARS: usize = 4096;
+/// How [`chunk_input`] sizes chunks for OpenAI.
+pub const DEFAULT_CHUNK_LIMIT: ChunkLimit = ChunkLimit::Chars(MAX_CHUNK_CHARS);
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, so `max_retries + 1` calls at most.
+    pub max_retries: u32,
+    pub backoff: Backoff,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            backoff: Backoff::default(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Chunk {index} failed after {attempts} attempt(s): {source}")]
+pub struct ChunkError {
+    /// Position of the failed chunk in the input, starting at 0.
+    pub index: usize,
+    pub attempts: u32,
+    #[source]
+    pub source: TtsError,
+}
+
+/// A piece of text to synthesize and the silence to play before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptChunk {
+    pub pause_before: Duration,
+    pub text: String,
+}
+
+/// The audio of one [`ScriptChunk`], preceded by its pause when the format
+/// allows writing silence.
+#[derive(Debug, Clone)]
+pub struct SpokenChunk {
+    pub silence: Option<Vec<u8>>,
+    pub audio: Vec<u8>,
+}
+
+impl SpokenChunk {
+    /// Silence (if any) then speech, each a complete chunk for [`concat_audio`].
+    pub fn into_parts(self) -> impl Iterator<Item = Vec<u8>> {
+        self.silence.into_iter().chain(Some(self.audio))
+    }
+}
+
+/// Splits user input at its pause markup, then normalizes every stretch of
+/// text into speakable words and chunks it to `limit`, the size the TTS
+/// provider accepts. A chunk only starts after a pause when it opens a new
+/// stretch of text.
+pub fn chunk_input(input: &str, normalizer: &Normalizer, limit: ChunkLimit) -> Vec<ScriptChunk> {
+    let mut chunks = Vec::new();
+    let mut pause = Duration::ZERO;
+
+    for segment in parse_markup(input) {
+        match segment {
+            Segment::Pause(duration) => pause = duration,
+            Segment::Text(text) => {
+                for text in
+                    chunk_text_sentences(&normalizer.normalize(&text), MAX_CHUNK_CHARS, Some(limit))
+                {
+                    chunks.push(ScriptChunk {
+                        pause_before: std::mem::take(&mut pause),
+                        text,
+                    });
+                }
+            }
+        }
+    }
+    chunks
+}
+
+/// `duration` of silence shaped like `reference`, audio from the same request,
+/// so the two concatenate cleanly. `None` for Opus, AAC and FLAC, where silence
+/// can't be written without an encoder.
+pub fn silence_like(format: AudioFormat, reference: &[u8], duration: Duration) -> Option<Vec<u8>> {
+    match format {
+        AudioFormat::Mp3 => {
+            let frame = mp3_frame::frames(reference)
+                .into_iter()
+                .find(|frame| !frame.is_vbr_header())?;
+            Some(mp3_frame::silence(&frame.header, duration))
+        }
+        AudioFormat::Wav => {
+            let format = parse_wav(reference).ok()?.format;
+            Some(wav_from_samples(&format, &format.silence(duration)))
+        }
+        AudioFormat::Pcm => Some(PCM_FORMAT.silence(duration)),
+        AudioFormat::Opus | AudioFormat::Aac | AudioFormat::Flac => None,
+    }
+}
+
+/// Joins the audio of consecutive chunks with the concatenator for `format`.
+/// ADTS AAC and raw PCM are self-delimiting, so they are simply appended.
+pub fn concat_audio<B: AsRef<[u8]>>(format: AudioFormat, chunks: &[B]) -> io::Result<Vec<u8>> {
+    match format {
+        AudioFormat::Mp3 => concat_mp3_bytes(chunks),
+        AudioFormat::Opus => concat_ogg_opus(chunks),
+        AudioFormat::Flac => concat_flac(chunks),
+        AudioFormat::Wav => concat_wav(chunks),
+        AudioFormat::Aac | AudioFormat::Pcm => {
+            let mut out = Vec::with_capacity(chunks.iter().map(|c| c.as_ref().len()).sum());
+            for chunk in chunks {
+                out.extend_from_slice(chunk.as_ref());
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Whether audio in `format` can be sent while later chunks are still being
+/// synthesized: true for formats without a header that covers the whole file.
+pub fn can_stream(format: AudioFormat) -> bool {
+    matches!(
+        format,
+        AudioFormat::Mp3 | AudioFormat::Aac | AudioFormat::Pcm
+    )
+}
+
+/// Synthesizes `chunks` with up to `concurrency` requests in flight and yields
+/// their audio in input order, whichever request finishes first. Pauses come
+/// along as silence matching the chunk's audio; see [`silence_like`].
+pub fn synthesize_stream(
+    tts: Arc<dyn TtsProvider>,
+    chunks: Vec<ScriptChunk>,
+    options: SpeechOptions,
+    concurrency: usize,
+    policy: RetryPolicy,
+) -> impl Stream<Item = Result<SpokenChunk, ChunkError>> + Send + 'static {
+    let options = Arc::new(options);
+    stream::iter(chunks.into_iter().enumerate())
+        .map(move |(index, chunk)| {
+            let tts = tts.clone();
+            let options = options.clone();
+            async move {
+                let audio =
+                    synthesize_chunk(tts.as_ref(), index, &chunk.text, &options, &policy).await?;
+                let silence = if chunk.pause_before.is_zero() {
+                    None
+                } else {
+                    let silence = silence_like(options.format, &audio, chunk.pause_before);
+                    if silence.is_none() {
+                        debug!(
+                            "Dropping the {:?} pause before chunk {index}: no silence for {} audio",
+                            chunk.pause_before, options.format
+                        );
+                    }
+                    silence
+                };
+                Ok(SpokenChunk { silence, audio })
+            }
+        })
+        .buffered(concurrency.max(1))
+}
+
+/// One chunk with retries: transient errors back off exponentially, or for as
+/// long as the server's `Retry-After` asks, before trying again.
+async fn synthesize_chunk(
+    tts: &dyn TtsProvider,
+    index: usize,
+    chunk: &str,
+    options: &SpeechOptions,
+    policy: &RetryPolicy,
+) -> Result<Vec<u8>, ChunkError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match tts.synthesize(chunk, options).await {
+            Ok(audio) => {
+                debug!("Chunk {index} synthesized on attempt {attempt}");
+                return Ok(audio);
+            }
+            Err(e) if e.is_retryable() && attempt <= policy.max_retries => {
+                let delay = e.retry_after().map_or_else(
+                    || policy.backoff.jittered_delay(attempt - 1),
+                    |after| after.min(policy.backoff.max),
+                );
+                warn!("Chunk {index} attempt {attempt} failed ({e}); retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+            }
+            Err(source) => {
+                return Err(ChunkError {
+                    index,
+                    attempts: attempt,
+                    source,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backoff::Jitter;
+    use reqwest::StatusCode;
+    use std::{collections::HashMap, sync::Mutex};
+
+    /// How the scripted provider answers one input.
+    #[derive(Default)]
+    struct Script {
+        /// Returned by the first calls, in order, before the input succeeds.
+        failures: Vec<(StatusCode, Option<Duration>)>,
+        /// How long every call takes.
+        latency: Duration,
+    }
+
+    /// Answers each input by its [`Script`], with the input itself as the
+    /// audio, and records the order successful calls finish in.
+    #[derive(Default)]
+    struct ScriptedTts {
+        scripts: HashMap<&'static str, Script>,
+        calls: Mutex<HashMap<String, usize>>,
+        finished: Mutex<Vec<String>>,
+    }
+
+    impl ScriptedTts {
+        fn calls(&self, input: &str) -> usize {
+            self.calls.lock().unwrap().get(input).copied().unwrap_or(0)
+        }
+    }
+
+    #[axum::async_trait]
+    impl TtsProvider for ScriptedTts {
+        fn model(&self) -> &str {
+            "scripted"
+        }
+
+        async fn synthesize(
+            &self,
+            input: &str,
+            _options: &SpeechOptions,
+        ) -> Result<Vec<u8>, TtsError> {
+            let call = {
+                let mut calls = self.calls.lock().unwrap();
+                let count = calls.entry(input.to_string()).or_default();
+                *count += 1;
+                *count - 1
+            };
+            let script = self.scripts.get(input);
+            tokio::time::sleep(script.map_or(Duration::ZERO, |s| s.latency)).await;
+
+            if let Some(&(status, retry_after)) = script.and_then(|s| s.failures.get(call)) {
+                return Err(TtsError::Api {
+                    status,
+                    body: "scripted failure".to_string(),
+                    retry_after,
+                });
+            }
+            self.finished.lock().unwrap().push(input.to_string());
+            Ok(input.as_bytes().to_vec())
+        }
+    }
+
+    fn chunks(texts: &[&str]) -> Vec<ScriptChunk> {
+        texts
+            .iter()
+            .map(|text| ScriptChunk {
+                pause_before: Duration::ZERO,
+                text: text.to_string(),
+            })
+            .collect()
+    }
+
+    /// Retries quickly, and never waits longer than `max`.
+    fn policy(max_retries: u32, max: Duration) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            backoff: Backoff {
+                base: Duration::from_millis(1),
+                max,
+                factor: 2,
+                jitter: Jitter::None,
+            },
+        }
+    }
+
+    async fn run(
+        tts: &Arc<ScriptedTts>,
+        texts: &[&str],
+        concurrency: usize,
+        policy: RetryPolicy,
+    ) -> Vec<Result<SpokenChunk, ChunkError>> {
+        synthesize_stream(
+            tts.clone(),
+            chunks(texts),
+            SpeechOptions::default(),
+            concurrency,
+            policy,
+        )
+        .collect()
+        .await
+    }
+
+    #[tokio::test]
+    async fn audio_comes_back_in_input_order_whichever_chunk_finishes_first() {
+        let mut tts = ScriptedTts::default();
+        tts.scripts.insert(
+            "slow",
+            Script {
+                latency: Duration::from_millis(100),
+                ..Script::default()
+            },
+        );
+        let tts = Arc::new(tts);
+
+        let spoken = run(
+            &tts,
+            &["slow", "fast", "faster"],
+            3,
+            policy(0, Duration::ZERO),
+        )
+        .await;
+
+        assert_eq!(*tts.finished.lock().unwrap(), ["fast", "faster", "slow"]);
+        let audio: Vec<Vec<u8>> = spoken.into_iter().map(|c| c.unwrap().audio).collect();
+        assert_eq!(audio, [&b"slow"[..], b"fast", b"faster"]);
+    }
+
+    #[tokio::test]
+    async fn only_the_failing_chunk_is_retried() {
+        let mut tts = ScriptedTts::default();
+        tts.scripts.insert(
+            "flaky",
+            Script {
+                failures: vec![
+                    (StatusCode::TOO_MANY_REQUESTS, None),
+                    (StatusCode::BAD_GATEWAY, None),
+                ],
+                ..Script::default()
+            },
+        );
+        let tts = Arc::new(tts);
+
+        let spoken = run(
+            &tts,
+            &["steady", "flaky", "calm"],
+            2,
+            policy(2, Duration::from_millis(10)),
+        )
+        .await;
+
+        assert!(spoken.iter().all(Result::is_ok));
+        assert_eq!(tts.calls("flaky"), 3);
+        assert_eq!(tts.calls("steady"), 1);
+        assert_eq!(tts.calls("calm"), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_after_is_capped_at_the_backoff_maximum() {
+        let mut tts = ScriptedTts::default();
+        tts.scripts.insert(
+            "limited",
+            Script {
+                failures: vec![(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Some(Duration::from_secs(60 * 60)),
+                )],
+                ..Script::default()
+            },
+        );
+        let tts = Arc::new(tts);
+
+        let spoken = tokio::time::timeout(
+            Duration::from_secs(5),
+            run(&tts, &["limited"], 1, policy(1, Duration::from_millis(10))),
+        )
+        .await
+        .expect("waited out the hour-long Retry-After");
+
+        assert!(spoken[0].is_ok());
+        assert_eq!(tts.calls("limited"), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_name_the_failing_chunk() {
+        let mut tts = ScriptedTts::default();
+        tts.scripts.insert(
+            "rejected",
+            Script {
+                failures: vec![(StatusCode::BAD_REQUEST, None)],
+                ..Script::default()
+            },
+        );
+        tts.scripts.insert(
+            "overloaded",
+            Script {
+                failures: vec![(StatusCode::SERVICE_UNAVAILABLE, None); 3],
+                ..Script::default()
+            },
+        );
+        let tts = Arc::new(tts);
+
+        let spoken = run(
+            &tts,
+            &["fine", "rejected", "overloaded"],
+            3,
+            policy(2, Duration::from_millis(10)),
+        )
+        .await;
+
+        assert!(spoken[0].is_ok());
+        // Not retryable: one attempt.
+        let e = spoken[1].as_ref().unwrap_err();
+        assert_eq!((e.index, e.attempts), (1, 1));
+        assert!(e
+            .to_string()
+            .starts_with("Chunk 1 failed after 1 attempt(s)"));
+        // Retryable, but out of retries.
+        let e = spoken[2].as_ref().unwrap_err();
+        assert_eq!((e.index, e.attempts), (2, 3));
+    }
+}
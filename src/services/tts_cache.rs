@@ -0,0 +1,494 @@
+// src/services/tts_cache.rs
+//! Content-addressed cache in front of a [`TtsProvider`]: identical text,
+//! voice, model and format are only ever synthesized once.
+
+use anyhow::Result as AnyResult;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+use tracing::{debug, warn};
+
+use crate::services::tts_service::{SpeechOptions, TtsError, TtsProvider};
+
+/// Extension of the files [`FsTtsCache`] keeps; the format is part of the key.
+const FILE_EXTENSION: &str = "audio";
+
+/// Hex SHA-256 of the inputs that determine the audio. Text is trimmed and
+/// runs of whitespace collapsed, so formatting differences still hit.
+pub fn cache_key(model: &str, options: &SpeechOptions, text: &str) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let speed = options.speed.to_string();
+
+    let mut hasher = Sha256::new();
+    for part in [
+        model,
+        &options.voice,
+        options.format.as_str(),
+        &speed,
+        &normalized,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Where cached audio lives.
+#[axum::async_trait]
+pub trait TtsCache: Send + Sync {
+    /// Returns the entry and marks it as recently used.
+    async fn get(&self, key: &str) -> AnyResult<Option<Vec<u8>>>;
+    async fn put(&self, key: &str, audio: &[u8]) -> AnyResult<()>;
+    /// Drops least recently used entries until at most `max_bytes` remain;
+    /// returns how many were removed.
+    async fn evict(&self, max_bytes: u64) -> AnyResult<u64>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CacheLimits {
+    /// Total size the cache is trimmed back to after every insert.
+    pub max_total_bytes: u64,
+    /// Larger clips are passed through without being cached.
+    pub max_entry_bytes: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 512 * 1024 * 1024,
+            max_entry_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CacheMetrics {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    errors: AtomicU64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheStats {
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    errors: u64,
+    hit_rate: f64,
+}
+
+impl CacheMetrics {
+    pub fn snapshot(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+
+        CacheStats {
+            hits,
+            misses,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                hits as f64 / lookups as f64
+            },
+        }
+    }
+}
+
+/// A [`TtsProvider`] that serves repeats from a [`TtsCache`]. Cache failures
+/// are logged and counted, never surfaced: the inner provider is the fallback.
+pub struct CachedTts {
+    inner: Arc<dyn TtsProvider>,
+    cache: Arc<dyn TtsCache>,
+    limits: CacheLimits,
+    metrics: Arc<CacheMetrics>,
+}
+
+impl CachedTts {
+    pub fn new(
+        inner: Arc<dyn TtsProvider>,
+        cache: Arc<dyn TtsCache>,
+        limits: CacheLimits,
+        metrics: Arc<CacheMetrics>,
+    ) -> Self {
+        Self {
+            inner,
+            cache,
+            limits,
+            metrics,
+        }
+    }
+
+    fn record_error(&self, what: &str, e: anyhow::Error) {
+        warn!("TTS cache {what} failed: {e:#}");
+        self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[axum::async_trait]
+impl TtsProvider for CachedTts {
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn model_for<'a>(&'a self, options: &SpeechOptions) -> &'a str {
+        self.inner.model_for(options)
+    }
+
+    async fn synthesize(&self, input: &str, options: &SpeechOptions) -> Result<Vec<u8>, TtsError> {
+        let key = cache_key(self.inner.model_for(options), options, input);
+
+        match self.cache.get(&key).await {
+            Ok(Some(audio)) => {
+                debug!("TTS cache hit for {key}");
+                self.metrics.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(audio);
+            }
+            Ok(None) => {}
+            Err(e) => self.record_error("lookup", e),
+        }
+        self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+
+        let audio = self.inner.synthesize(input, options).await?;
+        if audio.len() > self.limits.max_entry_bytes {
+            return Ok(audio);
+        }
+
+        match self.cache.put(&key, &audio).await {
+            Ok(()) => match self.cache.evict(self.limits.max_total_bytes).await {
+                Ok(evicted) => {
+                    self.metrics.evictions.fetch_add(evicted, Ordering::Relaxed);
+                }
+                Err(e) => self.record_error("eviction", e),
+            },
+            Err(e) => self.record_error("insert", e),
+        }
+
+        Ok(audio)
+    }
+}
+
+/// Cache rows in the `tts_cache` table.
+pub struct PgTtsCache {
+    db: PgPool,
+}
+
+impl PgTtsCache {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+#[axum::async_trait]
+impl TtsCache for PgTtsCache {
+    async fn get(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let audio = sqlx::query_scalar(
+            "UPDATE tts_cache SET last_used_at = NOW() WHERE key = $1 RETURNING audio",
+        )
+        .bind(key)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(audio)
+    }
+
+    async fn put(&self, key: &str, audio: &[u8]) -> AnyResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO tts_cache (key, audio, size_bytes)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (key) DO UPDATE SET last_used_at = NOW()
+            "#,
+        )
+        .bind(key)
+        .bind(audio)
+        .bind(audio.len() as i64)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn evict(&self, max_bytes: u64) -> AnyResult<u64> {
+        // Keep the most recently used entries whose running total fits.
+        let result = sqlx::query(
+            r#"
+            DELETE FROM tts_cache WHERE key IN (
+                SELECT key FROM (
+                    SELECT key, SUM(size_bytes) OVER (ORDER BY last_used_at DESC, key) AS running
+                    FROM tts_cache
+                ) ranked
+                WHERE running > $1
+            )
+            "#,
+        )
+        .bind(i64::try_from(max_bytes).unwrap_or(i64::MAX))
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// One file per entry in `dir`; the modification time doubles as "last used".
+pub struct FsTtsCache {
+    dir: PathBuf,
+}
+
+impl FsTtsCache {
+    pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{FILE_EXTENSION}"))
+    }
+}
+
+#[axum::async_trait]
+impl TtsCache for FsTtsCache {
+    async fn get(&self, key: &str) -> AnyResult<Option<Vec<u8>>> {
+        let path = self.path(key);
+        let found = tokio::task::spawn_blocking(move || -> std::io::Result<Option<Vec<u8>>> {
+            let audio = match fs::read(&path) {
+                Ok(audio) => audio,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            fs::File::options()
+                .write(true)
+                .open(&path)?
+                .set_modified(SystemTime::now())?;
+            Ok(Some(audio))
+        })
+        .await??;
+
+        Ok(found)
+    }
+
+    async fn put(&self, key: &str, audio: &[u8]) -> AnyResult<()> {
+        let path = self.path(key);
+        // Unique per writer, so concurrent puts of one key don't interleave.
+        let tmp = self
+            .dir
+            .join(format!("{key}.{:016x}.tmp", rand::random::<u64>()));
+        let audio = audio.to_vec();
+        tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            // Write then rename, so readers never see a half-written file.
+            fs::write(&tmp, audio)?;
+            fs::rename(&tmp, &path)
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    async fn evict(&self, max_bytes: u64) -> AnyResult<u64> {
+        let dir = self.dir.clone();
+        let evicted = tokio::task::spawn_blocking(move || -> std::io::Result<u64> {
+            let mut entries = Vec::new();
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                // Another sweep may have removed it since it was listed.
+                let meta = match entry.metadata() {
+                    Ok(meta) => meta,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+                // Anything but a half-written file counts, so entries from
+                // older naming schemes age out too.
+                let is_tmp = entry.path().extension().and_then(|e| e.to_str()) == Some("tmp");
+                if meta.is_file() && !is_tmp {
+                    entries.push((meta.modified()?, meta.len(), entry.path()));
+                }
+            }
+
+            // Newest first; everything past the budget goes.
+            entries.sort_by_key(|e| std::cmp::Reverse(e.0));
+            let mut total = 0u64;
+            let mut evicted = 0u64;
+            for (_, len, path) in entries {
+                total += len;
+                if total > max_bytes {
+                    match fs::remove_file(path) {
+                        Ok(()) => evicted += 1,
+                        // Already evicted by a concurrent sweep.
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Ok(evicted)
+        })
+        .await??;
+
+        Ok(evicted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tts_service::AudioFormat;
+    use std::{sync::Mutex, time::Duration};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crm-tts-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn keys_ignore_whitespace_differences() {
+        let options = SpeechOptions::default();
+
+        assert_eq!(
+            cache_key("tts-1", &options, "Hello,   world."),
+            cache_key("tts-1", &options, "  Hello,\n world.\t")
+        );
+        assert_eq!(cache_key("tts-1", &options, "Hi").len(), 64);
+    }
+
+    #[test]
+    fn keys_separate_everything_that_changes_the_audio() {
+        let options = SpeechOptions::default();
+        let key = cache_key("tts-1", &options, "Hello");
+
+        let other_voice = SpeechOptions::new("nova");
+        let other_format = SpeechOptions {
+            format: AudioFormat::Wav,
+            ..SpeechOptions::default()
+        };
+        let other_speed = SpeechOptions {
+            speed: 1.5,
+            ..SpeechOptions::default()
+        };
+        for other in [
+            cache_key("tts-1-hd", &options, "Hello"),
+            cache_key("tts-1", &other_voice, "Hello"),
+            cache_key("tts-1", &other_format, "Hello"),
+            cache_key("tts-1", &other_speed, "Hello"),
+            cache_key("tts-1", &options, "hello"),
+        ] {
+            assert_ne!(key, other);
+        }
+        // Parts are delimited, so moving text from one to the next matters.
+        assert_ne!(
+            cache_key("tts-1", &SpeechOptions::new("ab"), "c"),
+            cache_key("tts-1", &SpeechOptions::new("a"), "bc")
+        );
+    }
+
+    #[tokio::test]
+    async fn fs_cache_evicts_least_recently_used_first() {
+        let dir = temp_dir("lru");
+        let cache = FsTtsCache::new(&dir).unwrap();
+        // Written an hour, then minutes, ago: "a" is the oldest.
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(60 * 60);
+        for (i, key) in ["a", "b", "c"].into_iter().enumerate() {
+            cache.put(key, &[0; 10]).await.unwrap();
+            fs::File::options()
+                .write(true)
+                .open(cache.path(key))
+                .unwrap()
+                .set_modified(an_hour_ago + Duration::from_secs(60 * i as u64))
+                .unwrap();
+        }
+
+        // Reading "a" makes "b" the least recently used.
+        assert!(cache.get("a").await.unwrap().is_some());
+        assert_eq!(cache.evict(20).await.unwrap(), 1);
+        assert!(cache.get("b").await.unwrap().is_none());
+        assert!(cache.get("a").await.unwrap().is_some());
+        assert!(cache.get("c").await.unwrap().is_some());
+
+        assert_eq!(cache.evict(0).await.unwrap(), 2);
+        assert_eq!(cache.evict(0).await.unwrap(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn concurrent_sweeps_and_puts_dont_fail() {
+        let dir = temp_dir("concurrent");
+        let cache = FsTtsCache::new(&dir).unwrap();
+        for key in ["a", "b", "c", "d"] {
+            cache.put(key, &[0; 10]).await.unwrap();
+        }
+
+        let (first, second, put, again) = tokio::join!(
+            cache.evict(0),
+            cache.evict(0),
+            cache.put("e", &[1; 10]),
+            cache.put("e", &[1; 10]),
+        );
+        put.unwrap();
+        again.unwrap();
+        assert!(first.unwrap() + second.unwrap() >= 4);
+        assert!(fs::read_dir(&dir).unwrap().all(|entry| entry
+            .unwrap()
+            .path()
+            .extension()
+            .unwrap()
+            != "tmp"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Counts its calls and answers with the input.
+    #[derive(Default)]
+    struct CountingTts {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[axum::async_trait]
+    impl TtsProvider for CountingTts {
+        fn model(&self) -> &str {
+            "counting"
+        }
+
+        async fn synthesize(
+            &self,
+            input: &str,
+            _options: &SpeechOptions,
+        ) -> Result<Vec<u8>, TtsError> {
+            self.calls.lock().unwrap().push(input.to_string());
+            Ok(input.as_bytes().to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_tts_only_synthesizes_misses() {
+        let dir = temp_dir("cached");
+        let inner = Arc::new(CountingTts::default());
+        let metrics = Arc::new(CacheMetrics::default());
+        let tts = CachedTts::new(
+            inner.clone(),
+            Arc::new(FsTtsCache::new(&dir).unwrap()),
+            CacheLimits::default(),
+            metrics.clone(),
+        );
+        let options = SpeechOptions::default();
+
+        assert_eq!(tts.synthesize("Hello", &options).await.unwrap(), b"Hello");
+        assert_eq!(tts.synthesize(" Hello ", &options).await.unwrap(), b"Hello");
+        assert_eq!(tts.synthesize("Bye", &options).await.unwrap(), b"Bye");
+
+        assert_eq!(*inner.calls.lock().unwrap(), ["Hello", "Bye"]);
+        let stats = metrics.snapshot();
+        assert_eq!((stats.hits, stats.misses, stats.errors), (1, 2, 0));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,43 +1,308 @@
 // src/services/tts_service.rs
-use reqwest::Client;
-use serde::Serialize;
+use reqwest::{header::RETRY_AFTER, Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::LazyLock, time::Duration};
+use thiserror::Error;
+
+use crate::utils::concat_wav::WavFormat;
+
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+pub const DEFAULT_MODEL: &str = "tts-1";
+pub const DEFAULT_VOICE: &str = "alloy";
+/// Playback speeds the OpenAI speech endpoint accepts.
+pub const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+/// Sample format of OpenAI's `pcm` output (and of the samples in its `wav`).
+pub const PCM_FORMAT: WavFormat = WavFormat::pcm16(24_000, 1);
+
+/// One `reqwest::Client` (and its connection pool) for every TTS call.
+static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+/// The `response_format`s the OpenAI speech endpoint can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    /// Opus in an Ogg container.
+    Opus,
+    /// AAC in ADTS frames.
+    Aac,
+    Flac,
+    Wav,
+    /// Raw 24 kHz 16-bit signed little-endian mono samples.
+    Pcm,
+}
+
+impl AudioFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Aac => "aac",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Pcm => "pcm",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus => "audio/ogg",
+            AudioFormat::Aac => "audio/aac",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Pcm => "application/octet-stream",
+        }
+    }
+
+    /// File extension for downloads; Opus comes in an `.ogg` container.
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Opus => "ogg",
+            other => other.as_str(),
+        }
+    }
+
+    /// The format [`extension`](Self::extension) names.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "mp3" => Some(AudioFormat::Mp3),
+            "ogg" => Some(AudioFormat::Opus),
+            "aac" => Some(AudioFormat::Aac),
+            "flac" => Some(AudioFormat::Flac),
+            "wav" => Some(AudioFormat::Wav),
+            "pcm" => Some(AudioFormat::Pcm),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for AudioFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mp3" => Ok(AudioFormat::Mp3),
+            "opus" => Ok(AudioFormat::Opus),
+            "aac" => Ok(AudioFormat::Aac),
+            "flac" => Ok(AudioFormat::Flac),
+            "wav" => Ok(AudioFormat::Wav),
+            "pcm" => Ok(AudioFormat::Pcm),
+            other => Err(format!("Unknown audio format: {other}")),
+        }
+    }
+}
+
+/// OpenAI speech models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TtsModel {
+    #[serde(rename = "tts-1")]
+    Tts1,
+    #[serde(rename = "tts-1-hd")]
+    Tts1Hd,
+    #[serde(rename = "gpt-4o-mini-tts")]
+    Gpt4oMiniTts,
+}
+
+impl TtsModel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TtsModel::Tts1 => "tts-1",
+            TtsModel::Tts1Hd => "tts-1-hd",
+            TtsModel::Gpt4oMiniTts => "gpt-4o-mini-tts",
+        }
+    }
+}
+
+impl std::str::FromStr for TtsModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tts-1" => Ok(TtsModel::Tts1),
+            "tts-1-hd" => Ok(TtsModel::Tts1Hd),
+            "gpt-4o-mini-tts" => Ok(TtsModel::Gpt4oMiniTts),
+            other => Err(format!("Unknown TTS model: {other}")),
+        }
+    }
+}
+
+/// How a piece of text should be spoken.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechOptions {
+    pub voice: String,
+    /// `None` uses the provider's own model.
+    pub model: Option<TtsModel>,
+    pub format: AudioFormat,
+    pub speed: f32,
+}
+
+impl SpeechOptions {
+    pub fn new(voice: impl Into<String>) -> Self {
+        Self {
+            voice: voice.into(),
+            model: None,
+            format: AudioFormat::default(),
+            speed: 1.0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.voice.trim().is_empty() {
+            return Err("Voice must not be empty.".to_string());
+        }
+        if !SPEED_RANGE.contains(&self.speed) {
+            return Err(format!(
+                "Speed must be between {} and {}.",
+                SPEED_RANGE.start(),
+                SPEED_RANGE.end()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SpeechOptions {
+    fn default() -> Self {
+        Self::new(DEFAULT_VOICE)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TtsError {
+    #[error("Request error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("This provider can't produce {0} audio")]
+    UnsupportedFormat(AudioFormat),
+    #[error("TTS request failed: {status} - {body}")]
+    Api {
+        status: StatusCode,
+        body: String,
+        /// How long the server asked us to wait, from its `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+}
+
+impl TtsError {
+    /// Whether trying again later might succeed: rate limits, server errors
+    /// and network hiccups are transient, bad requests and auth errors aren't.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TtsError::Request(e) => e.is_timeout() || e.is_connect(),
+            TtsError::Api { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            TtsError::UnsupportedFormat(_) => false,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            TtsError::Api { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Something that can turn text into audio.
+#[axum::async_trait]
+pub trait TtsProvider: Send + Sync {
+    /// The model used when [`SpeechOptions::model`] is `None`.
+    fn model(&self) -> &str;
+
+    /// The model `options` end up being synthesized with; part of the cache key.
+    fn model_for<'a>(&'a self, options: &SpeechOptions) -> &'a str {
+        match options.model {
+            Some(model) => model.as_str(),
+            None => self.model(),
+        }
+    }
+
+    async fn synthesize(&self, input: &str, options: &SpeechOptions) -> Result<Vec<u8>, TtsError>;
+}
 
 #[derive(Serialize)]
-struct TtsRequest {
+struct TtsRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: AudioFormat,
+    speed: f32,
+}
+
+/// The OpenAI `/audio/speech` endpoint, or anything that speaks its protocol.
+#[derive(Clone)]
+pub struct OpenAiTts {
+    client: Client,
+    base_url: String,
+    api_key: String,
     model: String,
-    input: String,
-    voice: String,
-}
-
-pub async fn call_openai_tts(
-    api_key: &str,
-    input_text: &str,
-    voice: &str,
-) -> Result<Vec<u8>, String> {
-    let client = Client::new();
-
-    let body = TtsRequest {
-        model: "tts-1".to_string(),
-        input: input_text.to_string(),
-        voice: voice.to_string(),
-    };
-
-    let resp = client
-        .post("https://api.openai.com/v1/audio/speech")
-        .bearer_auth(api_key)
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("Request error: {e}"))?;
-
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let text = resp.text().await.unwrap_or_default();
-        return Err(format!("TTS request failed: {status} - {text}"));
-    }
-
-    resp.bytes()
-        .await
-        .map(|b| b.to_vec())
-        .map_err(|e| format!("Unable to read TTS response bytes: {e}"))
+}
+
+impl OpenAiTts {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: HTTP_CLIENT.clone(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            api_key: api_key.into(),
+            model: DEFAULT_MODEL.to_string(),
+        }
+    }
+
+    /// Points the provider at another host, e.g. a proxy or a local stand-in.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[axum::async_trait]
+impl TtsProvider for OpenAiTts {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn synthesize(&self, input: &str, options: &SpeechOptions) -> Result<Vec<u8>, TtsError> {
+        let body = TtsRequest {
+            model: self.model_for(options),
+            input,
+            voice: &options.voice,
+            response_format: options.format,
+            speed: options.speed,
+        };
+
+        let resp = self
+            .client
+            .post(format!("{}/audio/speech", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            // Only the delay-seconds form; OpenAI doesn't send HTTP dates here.
+            let retry_after = resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+            let body = resp.text().await.unwrap_or_default();
+            return Err(TtsError::Api {
+                status,
+                body,
+                retry_after,
+            });
+        }
+
+        Ok(resp.bytes().await?.to_vec())
+    }
 }
@@ -1,12 +1,36 @@
+use apalis_sql::postgres::PostgresStorage;
 use axum::extract::FromRef;
 use axum_extra::extract::cookie::Key;
 use shuttle_openai::async_openai::{config::OpenAIConfig, Client};
-use sqlx::PgPool;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::sync::Arc;
+
+use crate::jobs::{follow_up::FollowUpReminder, registry, speech::SpeechJob};
+use crate::services::{
+    audio_storage::AudioStorage, speech_pipeline::DEFAULT_CHUNK_LIMIT, tts_cache::CacheMetrics,
+    tts_service::TtsProvider,
+};
+use crate::utils::{chunk_text_unicode::ChunkLimit, normalize_text::Normalizer};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub openai_client: Client<OpenAIConfig>,
+    pub tts: Arc<dyn TtsProvider>,
+    pub tts_cache_metrics: Arc<CacheMetrics>,
+    /// Applied to speech input before it's chunked.
+    pub normalizer: Arc<Normalizer>,
+    /// How much speech input goes in each TTS request.
+    pub chunk_limit: ChunkLimit,
+    /// Where finished speech audio is kept and signed URLs come from.
+    pub audio_storage: Arc<dyn AudioStorage>,
+    /// Queue for background speech generation, see [`crate::jobs::speech`].
+    pub speech_jobs: PostgresStorage<SpeechJob>,
+    /// Whether reminder dates the parser can't read go to DeepSeek, see
+    /// [`crate::services::date_agent`].
+    pub date_agent: bool,
+    /// Queue for users' follow-up reminders, see [`crate::jobs::follow_up`].
+    pub follow_ups: PostgresStorage<FollowUpReminder>,
     key: Key,
 }
 
@@ -14,18 +38,38 @@ impl AppState {
     pub async fn new(
         conn_string: String,
         openai_client: Client<OpenAIConfig>,
+        tts: Arc<dyn TtsProvider>,
+        audio_storage: Arc<dyn AudioStorage>,
     ) -> Result<Self, sqlx::Error> {
-        let db = PgPool::connect(&conn_string).await?;
+        let db = PgPoolOptions::new()
+            .min_connections(5)
+            .max_connections(5)
+            .connect(&conn_string)
+            .await?;
+        let speech_jobs = registry::storage(&db);
+        let follow_ups = registry::storage(&db);
 
         Ok(Self {
             db,
             openai_client,
+            tts,
+            tts_cache_metrics: Arc::default(),
+            normalizer: Arc::default(),
+            chunk_limit: DEFAULT_CHUNK_LIMIT,
+            audio_storage,
+            speech_jobs,
+            date_agent: false,
+            follow_ups,
             key: Key::generate(),
         })
     }
 
     pub async fn seed(&self) {
-        sqlx::migrate!().run(&self.db).await.unwrap();
+        // apalis keeps its own migrations in the same `_sqlx_migrations` table,
+        // so don't fail on versions we don't know about.
+        let mut migrator = sqlx::migrate!();
+        migrator.set_ignore_missing(true);
+        migrator.run(&self.db).await.unwrap();
     }
 }
 
@@ -0,0 +1,118 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// How much of each delay is randomized, so callers that failed together
+/// don't all retry at the same instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// Exactly the computed delay.
+    #[default]
+    None,
+    /// Anywhere between half the computed delay and all of it.
+    Equal,
+    /// Anywhere between zero and the computed delay.
+    Full,
+}
+
+/// Exponential backoff: `base * factor^attempt`, capped at `max`, then
+/// randomized by `jitter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub base: Duration,
+    pub max: Duration,
+    pub factor: u32,
+    pub jitter: Jitter,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Backoff {
+    pub const DEFAULT: Backoff = Backoff {
+        base: Duration::from_millis(500),
+        max: Duration::from_secs(30),
+        factor: 2,
+        jitter: Jitter::None,
+    };
+
+    /// Delay before retry number `attempt`, counting from 0, before jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base
+            .saturating_mul(self.factor.saturating_pow(attempt))
+            .min(self.max)
+    }
+
+    /// [`delay`](Self::delay) randomized according to `jitter`.
+    pub fn jittered_delay(&self, attempt: u32) -> Duration {
+        let delay = self.delay(attempt);
+        let floor = match self.jitter {
+            Jitter::None => return delay,
+            Jitter::Equal => delay / 2,
+            Jitter::Full => Duration::ZERO,
+        };
+        rand::thread_rng().gen_range(floor..=delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff(jitter: Jitter) -> Backoff {
+        Backoff {
+            base: Duration::from_secs(5),
+            max: Duration::from_secs(60),
+            factor: 2,
+            jitter,
+        }
+    }
+
+    #[test]
+    fn delays_grow_exponentially_up_to_the_cap() {
+        let delays: Vec<u64> = (0..6)
+            .map(|attempt| backoff(Jitter::None).delay(attempt).as_secs())
+            .collect();
+
+        assert_eq!(delays, [5, 10, 20, 40, 60, 60]);
+    }
+
+    #[test]
+    fn huge_attempts_saturate_at_the_cap() {
+        let backoff = Backoff {
+            factor: 10,
+            ..backoff(Jitter::None)
+        };
+
+        assert_eq!(backoff.delay(40), Duration::from_secs(60));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(60));
+        assert_eq!(backoff.jittered_delay(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn jitter_stays_within_its_bounds() {
+        for attempt in 0..8 {
+            let delay = backoff(Jitter::None).delay(attempt);
+            for _ in 0..100 {
+                let equal = backoff(Jitter::Equal).jittered_delay(attempt);
+                assert!(
+                    delay / 2 <= equal && equal <= delay,
+                    "{equal:?} for {delay:?}"
+                );
+                let full = backoff(Jitter::Full).jittered_delay(attempt);
+                assert!(full <= delay, "{full:?} for {delay:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn jitter_actually_varies() {
+        let delays: std::collections::HashSet<_> = (0..100)
+            .map(|_| backoff(Jitter::Full).jittered_delay(3))
+            .collect();
+
+        assert!(delays.len() > 1);
+    }
+}
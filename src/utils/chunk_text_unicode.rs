@@ -1,30 +1,246 @@
 use unicode_segmentation::UnicodeSegmentation;
 
-/// Splits `text` into chunks of up to `max_chars` *graphemes* each,
-/// ensuring you never break a Unicode character in half.
-pub fn chunk_text_unicode(text: &str, max_chars: usize) -> Vec<String> {
-    let mut results = Vec::new();
-    let mut current_chunk = String::with_capacity(max_chars);
-    let mut current_count = 0;
+/// An extra cap on chunk size, on top of the grapheme count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkLimit {
+    /// Unicode scalar values (`char`s), which is what the OpenAI speech API
+    /// counts as "characters".
+    Chars(usize),
+    /// UTF-8 encoded bytes.
+    Bytes(usize),
+}
+
+/// Splits `text` into chunks, preferring to cut at sentence boundaries, then
+/// at word boundaries, and only splitting inside a word when a single word
+/// doesn't fit; a Unicode character is never broken in half. Boundaries
+/// follow the Unicode segmentation rules (UAX #29).
+///
+/// Every chunk has at most `max_chars` graphemes and, if given, stays within
+/// `limit`. Whitespace around chunk edges is trimmed and blank chunks are dropped.
+pub fn chunk_text_sentences(
+    text: &str,
+    max_chars: usize,
+    limit: Option<ChunkLimit>,
+) -> Vec<String> {
+    let mut chunker = Chunker {
+        max_graphemes: max_chars.max(1),
+        limit,
+        chunks: Vec::new(),
+        current: String::new(),
+        size: Size::default(),
+    };
+
+    for sentence in text.split_sentence_bounds() {
+        chunker.push(sentence, Boundary::Sentence);
+    }
+    chunker.flush();
 
-    // Iterate over graphemes (what we usually call "characters")
-    for grapheme in text.graphemes(true) {
-        // If adding another grapheme would exceed `max_chars`,
-        // push the current chunk and start a fresh one.
-        if current_count >= max_chars {
-            results.push(current_chunk);
-            current_chunk = String::with_capacity(max_chars);
-            current_count = 0;
+    chunker.chunks
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Boundary {
+    Sentence,
+    Word,
+    Grapheme,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Size {
+    graphemes: usize,
+    chars: usize,
+    bytes: usize,
+}
+
+impl Size {
+    fn of(s: &str) -> Self {
+        Size {
+            graphemes: s.graphemes(true).count(),
+            chars: s.chars().count(),
+            bytes: s.len(),
         }
+    }
 
-        current_chunk.push_str(grapheme);
-        current_count += 1;
+    fn plus(self, other: Size) -> Self {
+        Size {
+            graphemes: self.graphemes + other.graphemes,
+            chars: self.chars + other.chars,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+struct Chunker {
+    max_graphemes: usize,
+    limit: Option<ChunkLimit>,
+    chunks: Vec<String>,
+    current: String,
+    size: Size,
+}
+
+impl Chunker {
+    fn fits(&self, size: Size) -> bool {
+        size.graphemes <= self.max_graphemes
+            && match self.limit {
+                Some(ChunkLimit::Chars(max)) => size.chars <= max,
+                Some(ChunkLimit::Bytes(max)) => size.bytes <= max,
+                None => true,
+            }
+    }
+
+    fn push(&mut self, piece: &str, boundary: Boundary) {
+        let size = Size::of(piece);
+
+        // Room left in the current chunk
+        if self.fits(self.size.plus(size)) {
+            self.append(piece, size);
+            return;
+        }
+
+        // Fits in a chunk of its own
+        if self.fits(size) {
+            self.flush();
+            self.append(piece, size);
+            return;
+        }
+
+        // Too big on its own: fall back to the next finer boundary
+        match boundary {
+            Boundary::Sentence => {
+                self.flush();
+                for word in piece.split_word_bounds() {
+                    self.push(word, Boundary::Word);
+                }
+            }
+            Boundary::Word => {
+                for grapheme in piece.graphemes(true) {
+                    self.push(grapheme, Boundary::Grapheme);
+                }
+            }
+            Boundary::Grapheme => {
+                // A single grapheme over the limit can't be split any further.
+                self.flush();
+                self.append(piece, size);
+            }
+        }
     }
 
-    // Push the final chunk if there's any leftover
-    if !current_chunk.is_empty() {
-        results.push(current_chunk);
+    fn append(&mut self, piece: &str, size: Size) {
+        self.current.push_str(piece);
+        self.size = self.size.plus(size);
     }
 
-    results
+    fn flush(&mut self) {
+        let chunk = self.current.trim();
+        if !chunk.is_empty() {
+            self.chunks.push(chunk.to_string());
+        }
+        self.current.clear();
+        self.size = Size::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(text: &str, max_chars: usize) -> Vec<String> {
+        chunk_text_sentences(text, max_chars, None)
+    }
+
+    #[test]
+    fn keeps_sentences_together() {
+        assert_eq!(
+            chunks("Hello there. How are you? Fine.", 20),
+            ["Hello there.", "How are you? Fine."]
+        );
+        assert_eq!(chunks("Short.", 20), ["Short."]);
+    }
+
+    #[test]
+    fn falls_back_to_words_then_graphemes() {
+        assert_eq!(
+            chunks("one two three four five", 10),
+            ["one two", "three four", "five"]
+        );
+        assert_eq!(chunks("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn splits_non_latin_sentences() {
+        assert_eq!(
+            chunks("今日は晴れです。明日は雨です。", 10),
+            ["今日は晴れです。", "明日は雨です。"]
+        );
+        assert_eq!(
+            chunks("Привет, мир. Как дела?", 12),
+            ["Привет, мир.", "Как дела?"]
+        );
+    }
+
+    #[test]
+    fn never_splits_a_grapheme() {
+        // "é" as e + combining acute: one grapheme, two chars.
+        let e = "e\u{301}";
+        assert_eq!(
+            chunks(&e.repeat(5), 2),
+            [e.repeat(2), e.repeat(2), e.to_string()]
+        );
+        assert_eq!(
+            chunk_text_sentences(&e.repeat(3), 10, Some(ChunkLimit::Chars(4))),
+            [e.repeat(2), e.to_string()]
+        );
+
+        // A family emoji is one grapheme of five chars; over the limit on its
+        // own, it's still kept whole.
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(
+            chunk_text_sentences(&format!("{family}{family}"), 10, Some(ChunkLimit::Chars(3))),
+            [family, family]
+        );
+    }
+
+    #[test]
+    fn respects_the_char_limit() {
+        let text = "Grüße aus Köln! Ça va très bien, merci. Ñandú corre rápido.";
+        for max in 1..=12 {
+            for chunk in chunk_text_sentences(text, 100, Some(ChunkLimit::Chars(max))) {
+                assert!(chunk.chars().count() <= max, "{chunk:?} is over {max}");
+            }
+        }
+    }
+
+    #[test]
+    fn respects_the_byte_limit() {
+        // Two bytes per Cyrillic letter, three per kana: a char limit of
+        // the same size would let these through.
+        assert_eq!(
+            chunk_text_sentences("Привет, мир. Как дела?", 100, Some(ChunkLimit::Bytes(24))),
+            ["Привет, мир.", "Как дела?"]
+        );
+        assert_eq!(
+            chunk_text_sentences("ありがとう", 100, Some(ChunkLimit::Bytes(7))),
+            ["あり", "がと", "う"]
+        );
+
+        let text = "Grüße aus Köln! Ça va très bien, merci. 今日は晴れです。";
+        for max in 3..=24 {
+            let chunks = chunk_text_sentences(text, 100, Some(ChunkLimit::Bytes(max)));
+            for chunk in &chunks {
+                assert!(chunk.len() <= max, "{chunk:?} is over {max} bytes");
+            }
+            assert_eq!(
+                chunks.concat().replace(' ', ""),
+                text.replace(' ', ""),
+                "nothing is lost at {max} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn trims_and_drops_blank_chunks() {
+        assert!(chunks("   \n\t  ", 10).is_empty());
+        assert!(chunks("", 10).is_empty());
+        assert_eq!(chunks("  padded.  ", 10), ["padded."]);
+    }
 }
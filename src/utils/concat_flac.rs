@@ -0,0 +1,409 @@
+// src/utils/concat_flac.rs
+//! FLAC joining. The first input's STREAMINFO block is kept with its totals
+//! recomputed, all other metadata is dropped, and every frame header is
+//! rewritten to count samples from the start of the joined stream.
+
+use std::io;
+
+const MARKER: &[u8; 4] = b"fLaC";
+const STREAMINFO: u8 = 0;
+const LAST_BLOCK: u8 = 0x80;
+const STREAMINFO_LEN: usize = 34;
+
+#[derive(Debug, Clone, Copy)]
+struct StreamInfo {
+    min_block: u16,
+    max_block: u16,
+    min_frame: u32,
+    max_frame: u32,
+    sample_rate: u32,
+    channels: u8,
+    bits_per_sample: u8,
+    total_samples: u64,
+}
+
+impl StreamInfo {
+    fn parse(b: &[u8]) -> Self {
+        let packed = u64::from_be_bytes(b[10..18].try_into().unwrap());
+        Self {
+            min_block: u16::from_be_bytes([b[0], b[1]]),
+            max_block: u16::from_be_bytes([b[2], b[3]]),
+            min_frame: u32::from_be_bytes([0, b[4], b[5], b[6]]),
+            max_frame: u32::from_be_bytes([0, b[7], b[8], b[9]]),
+            sample_rate: (packed >> 44) as u32,
+            channels: ((packed >> 41) & 0x7) as u8 + 1,
+            bits_per_sample: ((packed >> 36) & 0x1F) as u8 + 1,
+            total_samples: packed & 0xF_FFFF_FFFF,
+        }
+    }
+
+    /// The block with an all-zero MD5, which means "not computed".
+    fn to_bytes(self) -> [u8; STREAMINFO_LEN] {
+        let mut b = [0u8; STREAMINFO_LEN];
+        b[0..2].copy_from_slice(&self.min_block.to_be_bytes());
+        b[2..4].copy_from_slice(&self.max_block.to_be_bytes());
+        b[4..7].copy_from_slice(&self.min_frame.to_be_bytes()[1..]);
+        b[7..10].copy_from_slice(&self.max_frame.to_be_bytes()[1..]);
+        let packed = (u64::from(self.sample_rate) << 44)
+            | (u64::from(self.channels - 1) << 41)
+            | (u64::from(self.bits_per_sample - 1) << 36)
+            | (self.total_samples & 0xF_FFFF_FFFF);
+        b[10..18].copy_from_slice(&packed.to_be_bytes());
+        b
+    }
+}
+
+/// STREAMINFO and the offset of the first frame.
+fn parse_metadata(data: &[u8]) -> io::Result<(StreamInfo, usize)> {
+    if !data.starts_with(MARKER) {
+        return Err(invalid("not a FLAC stream"));
+    }
+
+    let mut info = None;
+    let mut pos = MARKER.len();
+    loop {
+        let header = data
+            .get(pos..pos + 4)
+            .ok_or_else(|| invalid("truncated metadata"))?;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let body = data
+            .get(pos + 4..pos + 4 + len)
+            .ok_or_else(|| invalid("truncated metadata"))?;
+        if header[0] & !LAST_BLOCK == STREAMINFO && len == STREAMINFO_LEN {
+            info = Some(StreamInfo::parse(body));
+        }
+        pos += 4 + len;
+        if header[0] & LAST_BLOCK != 0 {
+            break;
+        }
+    }
+
+    let info = info.ok_or_else(|| invalid("no STREAMINFO block"))?;
+    Ok((info, pos))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrameHeader {
+    /// Header length including its CRC-8.
+    len: usize,
+    block_size: u32,
+    /// Length of the coded frame/sample number at [`NUMBER_AT`].
+    number_len: usize,
+}
+
+/// The frame/sample number follows the four fixed header bytes.
+const NUMBER_AT: usize = 4;
+
+/// Parses the frame header at the start of `b`, if there is a valid one.
+fn parse_frame_header(b: &[u8]) -> Option<FrameHeader> {
+    if b.len() < 6 || b[0] != 0xFF || b[1] & 0xFE != 0xF8 || b[3] & 1 != 0 {
+        return None;
+    }
+    let block_code = b[2] >> 4;
+    let rate_code = b[2] & 0xF;
+    if block_code == 0 || rate_code == 0xF || b[3] >> 4 > 10 {
+        return None;
+    }
+
+    // UTF-8-style coded frame or sample number.
+    let number_len = match b[NUMBER_AT].leading_ones() {
+        0 => 1,
+        n @ 2..=7 => n as usize,
+        _ => return None,
+    };
+    let continuation = b.get(NUMBER_AT + 1..NUMBER_AT + number_len)?;
+    if continuation.iter().any(|&c| c & 0xC0 != 0x80) {
+        return None;
+    }
+
+    let mut at = NUMBER_AT + number_len;
+    let block_size = match block_code {
+        1 => 192,
+        2..=5 => 576 << (block_code - 2),
+        6 => {
+            at += 1;
+            u32::from(*b.get(at - 1)?) + 1
+        }
+        7 => {
+            at += 2;
+            u32::from(u16::from_be_bytes([*b.get(at - 2)?, *b.get(at - 1)?])) + 1
+        }
+        _ => 256 << (block_code - 8),
+    };
+    at += match rate_code {
+        12 => 1,
+        13 | 14 => 2,
+        _ => 0,
+    };
+
+    (crc8(b.get(..at)?) == *b.get(at)?).then_some(FrameHeader {
+        len: at + 1,
+        block_size,
+        number_len,
+    })
+}
+
+/// Splits the audio after the metadata into frames. A frame ends where the
+/// next valid header starts and its own CRC-16 checks out; one too short to
+/// hold that CRC after its header is corrupt.
+fn frames(data: &[u8], start: usize) -> io::Result<Vec<(&[u8], FrameHeader)>> {
+    let mut frames = Vec::new();
+    let mut pos = start;
+    while pos < data.len() {
+        let header = parse_frame_header(&data[pos..])
+            .ok_or_else(|| invalid(&format!("no frame header at byte {pos}")))?;
+
+        let mut crc = crc16_update(0, &data[pos..pos + header.len]);
+        let mut end = data.len();
+        for p in pos + header.len..data.len() {
+            if crc == 0 && data[p] == 0xFF && parse_frame_header(&data[p..]).is_some() {
+                end = p;
+                break;
+            }
+            crc = crc16_update(crc, &data[p..=p]);
+        }
+        if end - pos < header.len + 2 || crc16_update(0, &data[pos..end]) != 0 {
+            return Err(invalid(&format!("frame at byte {pos} is corrupt")));
+        }
+
+        frames.push((&data[pos..end], header));
+        pos = end;
+    }
+    Ok(frames)
+}
+
+/// Joins complete FLAC buffers into one stream. Every input must have the same
+/// sample rate, channel count and bit depth, otherwise an `InvalidData` error
+/// is returned. Nothing is decoded; the MD5 signature is left unset.
+pub fn concat_flac<B: AsRef<[u8]>>(chunks: &[B]) -> io::Result<Vec<u8>> {
+    let mut merged: Option<StreamInfo> = None;
+    let mut out_frames = Vec::new();
+    let mut sample = 0u64;
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let data = chunk.as_ref();
+        let name = format!("chunk {i}");
+        let (info, start) = parse_metadata(data).map_err(|e| invalid(&format!("{name}: {e}")))?;
+
+        match &mut merged {
+            Some(m) => {
+                if (m.sample_rate, m.channels, m.bits_per_sample)
+                    != (info.sample_rate, info.channels, info.bits_per_sample)
+                {
+                    return Err(invalid(&format!(
+                        "{name}: {} Hz / {} channel(s) / {} bit doesn't match {} Hz / {} channel(s) / {} bit of the first chunk",
+                        info.sample_rate, info.channels, info.bits_per_sample,
+                        m.sample_rate, m.channels, m.bits_per_sample,
+                    )));
+                }
+                m.min_block = m.min_block.min(info.min_block);
+                m.max_block = m.max_block.max(info.max_block);
+            }
+            None => merged = Some(info),
+        }
+
+        for (frame, header) in frames(data, start).map_err(|e| invalid(&format!("{name}: {e}")))? {
+            out_frames.push(renumber(frame, &header, sample));
+            sample += u64::from(header.block_size);
+        }
+    }
+
+    let Some(mut info) = merged else {
+        return Err(invalid("nothing to join"));
+    };
+    info.total_samples = sample;
+    info.min_frame = out_frames.iter().map(|f| f.len() as u32).min().unwrap_or(0);
+    info.max_frame = out_frames.iter().map(|f| f.len() as u32).max().unwrap_or(0);
+
+    let mut out =
+        Vec::with_capacity(8 + STREAMINFO_LEN + out_frames.iter().map(Vec::len).sum::<usize>());
+    out.extend_from_slice(MARKER);
+    out.extend_from_slice(&[LAST_BLOCK | STREAMINFO, 0, 0, STREAMINFO_LEN as u8]);
+    out.extend_from_slice(&info.to_bytes());
+    for frame in out_frames {
+        out.extend_from_slice(&frame);
+    }
+    Ok(out)
+}
+
+/// Rewrites a frame to the variable block size strategy, where the header
+/// carries the number of its first sample, and fixes up both CRCs.
+fn renumber(frame: &[u8], header: &FrameHeader, sample: u64) -> Vec<u8> {
+    let mut out = Vec::with_capacity(frame.len() + 4);
+    out.extend_from_slice(&frame[..NUMBER_AT]);
+    out[1] |= 0x01;
+    encode_number(sample, &mut out);
+    out.extend_from_slice(&frame[NUMBER_AT + header.number_len..header.len - 1]);
+    out.push(crc8(&out));
+
+    out.extend_from_slice(&frame[header.len..frame.len() - 2]);
+    let crc = crc16_update(0, &out);
+    out.extend_from_slice(&crc.to_be_bytes());
+    out
+}
+
+/// FLAC's UTF-8-like variable-length integer coding (up to 36 bits).
+fn encode_number(n: u64, out: &mut Vec<u8>) {
+    if n < 0x80 {
+        out.push(n as u8);
+        return;
+    }
+    let len: u32 = if n < 0x800 {
+        2
+    } else if n < 0x1_0000 {
+        3
+    } else if n < 0x20_0000 {
+        4
+    } else if n < 0x400_0000 {
+        5
+    } else if n < 0x8000_0000 {
+        6
+    } else {
+        7
+    };
+    let mark = (0xFF00u16 >> len) as u8;
+    out.push(mark | (n >> (6 * (len - 1))) as u8);
+    for i in (0..len - 1).rev() {
+        out.push(0x80 | ((n >> (6 * i)) & 0x3F) as u8);
+    }
+}
+
+/// CRC-8, polynomial 0x07, over a frame header.
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |crc, &byte| {
+        let mut crc = crc ^ byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// CRC-16, polynomial 0x8005, over a whole frame. Including the frame's own
+/// trailing CRC yields 0.
+fn crc16_update(crc: u16, data: &[u8]) -> u16 {
+    data.iter().fold(crc, |crc, &byte| {
+        let mut crc = crc ^ (u16::from(byte) << 8);
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fixed 192-sample blocks, 16-bit mono at `sample_rate`.
+    fn stream_info(sample_rate: u32, frames: u64) -> StreamInfo {
+        StreamInfo {
+            min_block: 192,
+            max_block: 192,
+            min_frame: 0,
+            max_frame: 0,
+            sample_rate,
+            channels: 1,
+            bits_per_sample: 16,
+            total_samples: frames * 192,
+        }
+    }
+
+    /// A frame header numbering frame `number` of the fixed block size
+    /// strategy: 192 samples at 44.1 kHz, mono, 16 bits.
+    fn frame_header(number: u64) -> Vec<u8> {
+        let mut header = vec![0xFF, 0xF8, 0x19, 0x08];
+        encode_number(number, &mut header);
+        header.push(crc8(&header));
+        header
+    }
+
+    /// A frame of silence: one constant subframe of 0.
+    fn frame(number: u64) -> Vec<u8> {
+        let mut frame = frame_header(number);
+        frame.extend([0x00, 0x00, 0x00]);
+        let crc = crc16_update(0, &frame);
+        frame.extend(crc.to_be_bytes());
+        frame
+    }
+
+    /// A FLAC stream with STREAMINFO, a padding block and `frames` frames.
+    fn flac(sample_rate: u32, frames: u64) -> Vec<u8> {
+        let mut out = MARKER.to_vec();
+        out.extend([STREAMINFO, 0, 0, STREAMINFO_LEN as u8]);
+        out.extend(stream_info(sample_rate, frames).to_bytes());
+        out.extend([LAST_BLOCK | 1, 0, 0, 8]);
+        out.extend([0; 8]);
+        for number in 0..frames {
+            out.extend(frame(number));
+        }
+        out
+    }
+
+    #[test]
+    fn joins_and_renumbers_frames() {
+        let joined = concat_flac(&[flac(44100, 3), flac(44100, 2)]).unwrap();
+
+        let (info, start) = parse_metadata(&joined).unwrap();
+        assert_eq!(start, MARKER.len() + 4 + STREAMINFO_LEN);
+        assert_eq!(info.total_samples, 5 * 192);
+        assert_eq!((info.sample_rate, info.channels), (44100, 1));
+
+        let frames = frames(&joined, start).unwrap();
+        assert_eq!(frames.len(), 5);
+        for (i, (frame, header)) in frames.iter().enumerate() {
+            // Variable block size strategy, numbered by first sample.
+            assert_eq!(frame[1], 0xF9);
+            let mut number = Vec::new();
+            encode_number(i as u64 * 192, &mut number);
+            assert_eq!(&frame[NUMBER_AT..NUMBER_AT + header.number_len], number);
+            assert_eq!(&frame[header.len..frame.len() - 2], [0, 0, 0]);
+        }
+        let lens: Vec<u32> = frames.iter().map(|(f, _)| f.len() as u32).collect();
+        assert_eq!(info.min_frame, *lens.iter().min().unwrap());
+        assert_eq!(info.max_frame, *lens.iter().max().unwrap());
+
+        // The joined stream joins again.
+        let rejoined = concat_flac(&[joined, flac(44100, 1)]).unwrap();
+        assert_eq!(parse_metadata(&rejoined).unwrap().0.total_samples, 6 * 192);
+    }
+
+    #[test]
+    fn rejects_mismatched_or_corrupt_input() {
+        let mismatched = concat_flac(&[flac(44100, 1), flac(48000, 1)]);
+        assert_eq!(mismatched.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut corrupt = flac(44100, 2);
+        let last = corrupt.len() - 3;
+        corrupt[last] ^= 0x01;
+        assert!(concat_flac(&[corrupt]).is_err());
+
+        assert!(concat_flac(&[b"RIFF".to_vec()]).is_err());
+        assert!(concat_flac::<Vec<u8>>(&[]).is_err());
+    }
+
+    #[test]
+    fn rejects_frames_without_room_for_a_crc() {
+        // A lone header whose own CRC-16 happens to be 0 looks like a frame;
+        // frame number 2165943 gives one.
+        let header = frame_header(2_165_943);
+        assert_eq!(crc16_update(0, &header), 0);
+        let mut short = flac(44100, 0);
+        short.extend(header);
+
+        let e = concat_flac(&[short]).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,29 +1,270 @@
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use futures::{Stream, StreamExt};
+use std::io::{self, Write};
 
-/// Naive MP3 concatenation:
-/// Copies the raw bytes of each input file into the output file in order,
-/// without decoding or re-encoding.
+use crate::utils::mp3_frame::{self, Frame, FrameHeader, Layer};
+
+/// Xing flags: frame count, byte count and TOC fields are present.
+const XING_FLAGS: u32 = 0x0000_0007;
+/// Tag + flags + frames + bytes + 100-entry TOC.
+const XING_LEN: usize = 4 + 4 + 4 + 4 + 100;
+
+/// Frame-aware MP3 concatenation: joins complete MP3 buffers (e.g. the bytes
+/// returned by the TTS service) into one MP3. Each buffer's ID3v2/ID3v1 tags
+/// and Xing/Info/LAME (or VBRI) header frame are dropped, and the remaining
+/// audio frames are written in order behind one fresh Xing header that
+/// carries the true frame count, byte count and seek table.
 ///
-/// Returns an `std::io::Result<()>` indicating success/failure.
+/// Nothing is decoded or re-encoded. Every buffer must use the same sample
+/// rate and channel count, otherwise an `InvalidData` error is returned.
+pub fn concat_mp3_bytes<B: AsRef<[u8]>>(chunks: &[B]) -> io::Result<Vec<u8>> {
+    let mut frames = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        push_chunk(&mut frames, chunk.as_ref(), &format!("chunk {i}"))?;
+    }
+
+    let mut out = Vec::with_capacity(chunks.iter().map(|c| c.as_ref().len()).sum());
+    write_joined(&frames, &mut out)?;
+    Ok(out)
+}
+
+/// Streaming variant of [`concat_mp3_bytes`]: each item of `chunks` is one
+/// complete MP3 buffer, and the returned stream yields its audio frames (tags
+/// and VBR header frame removed) as soon as the item arrives.
 ///
 /// # Caveat
-/// This doesn't validate MP3 frames or remove ID3 tags.
-/// The resulting file may have playback issues if each chunk
-/// is a fully self-contained MP3 with its own headers.
-pub fn concat_mp3(input_files: &[&str], output_file: &str) -> std::io::Result<()> {
-    // Create or overwrite the output file
-    let mut out = BufWriter::new(File::create(output_file)?);
+/// The total length isn't known until the input ends, so no Xing header is
+/// written; players estimate duration from the bitrate instead. Use
+/// [`concat_mp3_bytes`] when the result is stored rather than streamed.
+pub fn concat_mp3_stream<S, B, E>(chunks: S) -> impl Stream<Item = Result<Vec<u8>, E>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: From<io::Error>,
+{
+    let mut first: Option<FrameHeader> = None;
+    let mut index = 0usize;
+
+    chunks.map(move |chunk| {
+        let chunk = chunk?;
+        let name = format!("chunk {index}");
+        index += 1;
+
+        let frames = audio_frames(chunk.as_ref());
+        let Some(header) = frames.first().map(|f| f.header) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{name}: no MPEG audio frames found"),
+            )
+            .into());
+        };
+        match &first {
+            Some(expected) => check_compatible(expected, &header, &name)?,
+            None => first = Some(header),
+        }
+
+        let mut out = Vec::with_capacity(chunk.as_ref().len());
+        for frame in frames {
+            out.extend_from_slice(frame.data);
+        }
+        Ok(out)
+    })
+}
+
+/// The audio frames of one MP3 chunk, without tags or a VBR header frame.
+pub(crate) fn audio_frames(data: &[u8]) -> Vec<Frame<'_>> {
+    let mut frames = mp3_frame::frames(data);
+    if frames.first().is_some_and(Frame::is_vbr_header) {
+        frames.remove(0);
+    }
+    frames
+}
 
-    for &mp3_path in input_files {
-        // Read each chunk as raw bytes
-        let mut f = BufReader::new(File::open(mp3_path)?);
+/// Appends the audio frames of `data` to `frames`, checking that the chunk
+/// is compatible with what has been collected so far.
+fn push_chunk<'a>(frames: &mut Vec<Frame<'a>>, data: &'a [u8], name: &str) -> io::Result<()> {
+    let chunk = audio_frames(data);
 
-        // Copy everything into output
-        std::io::copy(&mut f, &mut out)?;
+    let Some(first) = chunk.first().map(|f| f.header) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{name}: no MPEG audio frames found"),
+        ));
+    };
+    if let Some(expected) = frames.first().map(|f| f.header) {
+        check_compatible(&expected, &first, name)?;
     }
 
-    // Flush ensures all data is written
-    out.flush()?;
+    frames.extend(chunk);
     Ok(())
 }
+
+pub(crate) fn check_compatible(
+    expected: &FrameHeader,
+    actual: &FrameHeader,
+    name: &str,
+) -> io::Result<()> {
+    if expected.sample_rate != actual.sample_rate
+        || expected.channel_mode.channels() != actual.channel_mode.channels()
+        || expected.layer != actual.layer
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{name}: {} Hz / {} channel(s) doesn't match {} Hz / {} channel(s) of the first chunk",
+                actual.sample_rate,
+                actual.channel_mode.channels(),
+                expected.sample_rate,
+                expected.channel_mode.channels(),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn write_joined<W: Write>(frames: &[Frame<'_>], out: &mut W) -> io::Result<()> {
+    if let Some(header) = vbr_header_frame(frames) {
+        out.write_all(&header)?;
+    }
+    for frame in frames {
+        out.write_all(frame.data)?;
+    }
+    Ok(())
+}
+
+/// Builds a Xing header frame describing `frames`: "Xing" when bitrates vary,
+/// "Info" when they're constant. Players use it for duration and seeking.
+///
+/// Returns `None` for empty input and for layers other than III, which have
+/// no Xing header.
+pub(crate) fn vbr_header_frame(frames: &[Frame<'_>]) -> Option<Vec<u8>> {
+    let first = frames.first()?.header;
+    if first.layer != Layer::L3 {
+        return None;
+    }
+
+    // Smallest bitrate whose (unpadded, CRC-less) frame is big enough for the tag.
+    let template = FrameHeader {
+        crc: false,
+        padding: false,
+        ..first
+    };
+    let header = first
+        .bitrates()
+        .iter()
+        .map(|&bitrate_kbps| FrameHeader {
+            bitrate_kbps,
+            ..template
+        })
+        .find(|h| h.frame_len() >= h.xing_offset() + XING_LEN)?;
+
+    let frame_len = header.frame_len();
+    let total_bytes = frame_len + frames.iter().map(|f| f.data.len()).sum::<usize>();
+    let is_vbr = frames
+        .iter()
+        .any(|f| f.header.bitrate_kbps != first.bitrate_kbps);
+
+    // Byte offset (from the start of the file) of each audio frame.
+    let mut offsets = Vec::with_capacity(frames.len());
+    let mut pos = frame_len;
+    for frame in frames {
+        offsets.push(pos);
+        pos += frame.data.len();
+    }
+
+    // TOC entry i: where playback at i% lands, in 1/256ths of the file size.
+    let mut toc = [0u8; 100];
+    for (i, entry) in toc.iter_mut().enumerate() {
+        let frame = i * frames.len() / 100;
+        *entry = (offsets[frame] * 256 / total_bytes).min(255) as u8;
+    }
+
+    let mut buf = vec![0u8; frame_len];
+    buf[..4].copy_from_slice(&header.to_bytes());
+
+    let xing = header.xing_offset();
+    let fields = [
+        if is_vbr { &b"Xing"[..] } else { &b"Info"[..] },
+        &XING_FLAGS.to_be_bytes(),
+        &(frames.len() as u32).to_be_bytes(),
+        &(total_bytes as u32).to_be_bytes(),
+        &toc,
+    ];
+    let mut at = xing;
+    for field in fields {
+        buf[at..at + field.len()].copy_from_slice(field);
+        at += field.len();
+    }
+
+    Some(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mp3_frame::{silent_frame, ChannelMode, MpegVersion};
+
+    fn header(bitrate_kbps: u32, sample_rate: u32) -> FrameHeader {
+        FrameHeader {
+            version: MpegVersion::V1,
+            layer: Layer::L3,
+            crc: false,
+            bitrate_kbps,
+            sample_rate,
+            padding: false,
+            channel_mode: ChannelMode::Mono,
+        }
+    }
+
+    /// `count` silent frames of `header`, as one MP3 chunk.
+    fn chunk(header: &FrameHeader, count: usize) -> Vec<u8> {
+        silent_frame(header).repeat(count)
+    }
+
+    /// The tag, frame count and byte count of a Xing/Info header frame.
+    fn xing_fields(mp3: &[u8]) -> (&[u8], u32, u32) {
+        let header = FrameHeader::parse(mp3).unwrap();
+        let at = header.xing_offset();
+        let field = |i: usize| u32::from_be_bytes(mp3[at + i..at + i + 4].try_into().unwrap());
+        (&mp3[at..at + 4], field(8), field(12))
+    }
+
+    #[test]
+    fn writes_one_info_header_with_true_counts() {
+        let h = header(128, 44100);
+        let mut tagged = b"ID3\x04\x00\x00\x00\x00\x00\x0A".to_vec();
+        tagged.extend([0u8; 10]);
+        tagged.extend(chunk(&h, 2));
+        let joined = concat_mp3_bytes(&[chunk(&h, 3), tagged]).unwrap();
+
+        let (tag, frames, bytes) = xing_fields(&joined);
+        assert_eq!(tag, b"Info");
+        assert_eq!(frames, 5);
+        assert_eq!(bytes as usize, joined.len());
+
+        // Joining again replaces the old header rather than keeping both.
+        let rejoined = concat_mp3_bytes(&[joined, chunk(&h, 1)]).unwrap();
+        let (_, frames, bytes) = xing_fields(&rejoined);
+        assert_eq!(frames, 6);
+        assert_eq!(bytes as usize, rejoined.len());
+        assert_eq!(mp3_frame::frames(&rejoined).len(), 7);
+    }
+
+    #[test]
+    fn mixed_bitrates_get_a_xing_header() {
+        let joined =
+            concat_mp3_bytes(&[chunk(&header(128, 44100), 2), chunk(&header(64, 44100), 2)])
+                .unwrap();
+        let (tag, frames, _) = xing_fields(&joined);
+        assert_eq!(tag, b"Xing");
+        assert_eq!(frames, 4);
+    }
+
+    #[test]
+    fn rejects_incompatible_or_empty_chunks() {
+        let mismatched =
+            concat_mp3_bytes(&[chunk(&header(128, 44100), 1), chunk(&header(128, 48000), 1)]);
+        assert_eq!(mismatched.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let empty = concat_mp3_bytes(&[chunk(&header(128, 44100), 1), b"not audio".to_vec()]);
+        assert_eq!(empty.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,264 @@
+// src/utils/concat_ogg.rs
+//! Ogg Opus joining. The inputs are merged into one logical stream rather
+//! than chained: later inputs lose their `OpusHead`/`OpusTags` pages, their
+//! pages take over the first input's serial number and page sequence, and
+//! their granule positions continue where the previous input ended.
+
+use std::io;
+
+const CAPTURE: &[u8; 4] = b"OggS";
+const FLAG_BOS: u8 = 0x02;
+const FLAG_EOS: u8 = 0x04;
+/// Granule position of a page on which no packet ends.
+const NO_GRANULE: i64 = -1;
+
+#[derive(Debug, Clone, Copy)]
+struct Page<'a> {
+    header_type: u8,
+    granule: i64,
+    serial: u32,
+    lacing: &'a [u8],
+    body: &'a [u8],
+}
+
+fn pages(data: &[u8]) -> io::Result<Vec<Page<'_>>> {
+    let mut pages = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let header = data
+            .get(pos..pos + 27)
+            .filter(|h| &h[0..4] == CAPTURE && h[4] == 0)
+            .ok_or_else(|| invalid(&format!("no Ogg page at byte {pos}")))?;
+        let segments = usize::from(header[26]);
+        let lacing = data
+            .get(pos + 27..pos + 27 + segments)
+            .ok_or_else(|| invalid("truncated Ogg page"))?;
+        let body_start = pos + 27 + segments;
+        let body_len: usize = lacing.iter().map(|&l| usize::from(l)).sum();
+        let body = data
+            .get(body_start..body_start + body_len)
+            .ok_or_else(|| invalid("truncated Ogg page"))?;
+
+        pages.push(Page {
+            header_type: header[5],
+            granule: i64::from_le_bytes(header[6..14].try_into().unwrap()),
+            serial: u32::from_le_bytes(header[14..18].try_into().unwrap()),
+            lacing,
+            body,
+        });
+        pos = body_start + body_len;
+    }
+    Ok(pages)
+}
+
+/// Channel count from the `OpusHead` packet and the index of the first page
+/// after the two header packets (audio always starts on a fresh page).
+fn split_headers(pages: &[Page<'_>]) -> io::Result<(u8, usize)> {
+    let head = pages
+        .first()
+        .map(|p| p.body)
+        .filter(|b| b.len() >= 19 && b.starts_with(b"OpusHead"))
+        .ok_or_else(|| invalid("stream doesn't start with OpusHead"))?;
+
+    let mut packets = 0;
+    for (i, page) in pages.iter().enumerate() {
+        packets += page.lacing.iter().filter(|&&l| l < 255).count();
+        if packets >= 2 {
+            return Ok((head[9], i + 1));
+        }
+    }
+    Err(invalid("stream ends inside its header packets"))
+}
+
+/// Joins complete Ogg Opus buffers into one stream. Every input must have the
+/// same channel count, otherwise an `InvalidData` error is returned.
+///
+/// Each later input's pre-skip samples (a few milliseconds of encoder warm-up)
+/// end up audible at the joint; nothing is decoded or re-encoded.
+pub fn concat_ogg_opus<B: AsRef<[u8]>>(chunks: &[B]) -> io::Result<Vec<u8>> {
+    let mut out_pages: Vec<(u8, i64, &[u8], &[u8])> = Vec::new();
+    let mut channels = None;
+    let mut serial = 0;
+    let mut offset = 0i64;
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let name = format!("chunk {i}");
+        let pages = pages(chunk.as_ref()).map_err(|e| invalid(&format!("{name}: {e}")))?;
+        let (chunk_channels, audio_start) =
+            split_headers(&pages).map_err(|e| invalid(&format!("{name}: {e}")))?;
+        match channels {
+            Some(expected) if expected != chunk_channels => {
+                return Err(invalid(&format!(
+                    "{name}: {chunk_channels} channel(s) doesn't match {expected} channel(s) of the first chunk"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                channels = Some(chunk_channels);
+                serial = pages[0].serial;
+            }
+        }
+
+        let keep = if i == 0 {
+            &pages[..]
+        } else {
+            &pages[audio_start..]
+        };
+        let mut last_granule = 0;
+        for page in keep {
+            let mut header_type = page.header_type & !FLAG_EOS;
+            if i > 0 {
+                header_type &= !FLAG_BOS;
+            }
+            let granule = if page.granule == NO_GRANULE {
+                NO_GRANULE
+            } else {
+                last_granule = page.granule;
+                page.granule + offset
+            };
+            out_pages.push((header_type, granule, page.lacing, page.body));
+        }
+        offset += last_granule;
+    }
+
+    if let Some(last) = out_pages.last_mut() {
+        last.0 |= FLAG_EOS;
+    } else {
+        return Err(invalid("nothing to join"));
+    }
+
+    let mut out = Vec::with_capacity(chunks.iter().map(|c| c.as_ref().len()).sum());
+    for (sequence, (header_type, granule, lacing, body)) in out_pages.into_iter().enumerate() {
+        let start = out.len();
+        out.extend_from_slice(CAPTURE);
+        out.push(0);
+        out.push(header_type);
+        out.extend_from_slice(&granule.to_le_bytes());
+        out.extend_from_slice(&serial.to_le_bytes());
+        out.extend_from_slice(&(sequence as u32).to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.push(lacing.len() as u8);
+        out.extend_from_slice(lacing);
+        out.extend_from_slice(body);
+
+        let crc = ogg_crc(&out[start..]);
+        out[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+    }
+    Ok(out)
+}
+
+/// CRC-32 as used by Ogg: polynomial 0x04C11DB7, no reflection, zero initial
+/// value and no final XOR, computed with the checksum field zeroed.
+fn ogg_crc(page: &[u8]) -> u32 {
+    page.iter().fold(0u32, |crc, &byte| {
+        let mut crc = crc ^ (u32::from(byte) << 24);
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One page holding `packets`, each under 255 bytes.
+    fn page(
+        header_type: u8,
+        granule: i64,
+        serial: u32,
+        sequence: u32,
+        packets: &[&[u8]],
+    ) -> Vec<u8> {
+        let mut page = CAPTURE.to_vec();
+        page.extend([0, header_type]);
+        page.extend(granule.to_le_bytes());
+        page.extend(serial.to_le_bytes());
+        page.extend(sequence.to_le_bytes());
+        page.extend([0; 4]);
+        page.push(packets.len() as u8);
+        page.extend(packets.iter().map(|p| p.len() as u8));
+        for packet in packets {
+            page.extend(*packet);
+        }
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        page
+    }
+
+    /// OpusHead, OpusTags, then one page per entry of `granules`.
+    fn opus(channels: u8, serial: u32, granules: &[i64]) -> Vec<u8> {
+        let mut head = b"OpusHead\x01".to_vec();
+        head.push(channels);
+        head.extend([0x38, 0x01, 0x80, 0xBB, 0, 0, 0, 0, 0]);
+
+        let mut out = page(FLAG_BOS, 0, serial, 0, &[&head]);
+        out.extend(page(
+            0,
+            0,
+            serial,
+            1,
+            &[b"OpusTags\x00\x00\x00\x00\x00\x00\x00\x00"],
+        ));
+        for (i, &granule) in granules.iter().enumerate() {
+            let flags = if i + 1 == granules.len() { FLAG_EOS } else { 0 };
+            out.extend(page(
+                flags,
+                granule,
+                serial,
+                i as u32 + 2,
+                &[&[0xFC, i as u8]],
+            ));
+        }
+        out
+    }
+
+    #[test]
+    fn merges_into_one_logical_stream() {
+        let joined = concat_ogg_opus(&[opus(1, 7, &[960, 1920]), opus(1, 9, &[480, 960])]).unwrap();
+        let pages = pages(&joined).unwrap();
+
+        assert_eq!(pages.len(), 6);
+        assert!(pages.iter().all(|p| p.serial == 7));
+        let granules: Vec<i64> = pages.iter().map(|p| p.granule).collect();
+        assert_eq!(granules, [0, 0, 960, 1920, 2400, 2880]);
+        let flags: Vec<u8> = pages.iter().map(|p| p.header_type).collect();
+        assert_eq!(flags, [FLAG_BOS, 0, 0, 0, 0, FLAG_EOS]);
+        assert_eq!(pages[5].body, [0xFC, 1]);
+
+        // Every page is renumbered and carries a valid checksum.
+        let mut pos = 0;
+        for (sequence, p) in pages.iter().enumerate() {
+            let len = 27 + p.lacing.len() + p.body.len();
+            let mut bytes = joined[pos..pos + len].to_vec();
+            assert_eq!(
+                u32::from_le_bytes(bytes[18..22].try_into().unwrap()),
+                sequence as u32
+            );
+            let crc = u32::from_le_bytes(bytes[22..26].try_into().unwrap());
+            bytes[22..26].fill(0);
+            assert_eq!(ogg_crc(&bytes), crc);
+            pos += len;
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_or_invalid_input() {
+        let mismatched = concat_ogg_opus(&[opus(1, 7, &[960]), opus(2, 9, &[960])]);
+        assert_eq!(mismatched.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let truncated = opus(1, 7, &[960]);
+        assert!(concat_ogg_opus(&[&truncated[..truncated.len() - 1]]).is_err());
+        assert!(concat_ogg_opus(&[b"fLaC".to_vec()]).is_err());
+        assert!(concat_ogg_opus::<Vec<u8>>(&[]).is_err());
+    }
+}
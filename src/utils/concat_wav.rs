@@ -0,0 +1,255 @@
+// src/utils/concat_wav.rs
+//! WAV (RIFF) joining: the `data` chunks of every input are appended behind a
+//! single header whose sizes are rewritten to cover all of them.
+
+use std::{io, time::Duration};
+
+/// The parts of a `fmt ` chunk that have to agree for samples to be appended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavFormat {
+    /// 1 for integer PCM.
+    pub audio_format: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+}
+
+impl WavFormat {
+    /// Integer PCM, 16 bits per sample.
+    pub const fn pcm16(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            audio_format: 1,
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+        }
+    }
+
+    /// Bytes per sample frame (one sample for every channel).
+    pub fn block_align(&self) -> u16 {
+        self.channels * self.bits_per_sample.div_ceil(8)
+    }
+
+    /// Raw samples of silence lasting `duration`, rounded to whole sample frames.
+    /// 8-bit PCM is unsigned, so its midpoint is 0x80 rather than 0.
+    pub fn silence(&self, duration: Duration) -> Vec<u8> {
+        let frames = (duration.as_secs_f64() * f64::from(self.sample_rate)).round() as usize;
+        let fill = if self.bits_per_sample == 8 { 0x80 } else { 0 };
+        vec![fill; frames * usize::from(self.block_align())]
+    }
+
+    fn fmt_chunk(&self) -> [u8; 16] {
+        let mut fmt = [0u8; 16];
+        fmt[0..2].copy_from_slice(&self.audio_format.to_le_bytes());
+        fmt[2..4].copy_from_slice(&self.channels.to_le_bytes());
+        fmt[4..8].copy_from_slice(&self.sample_rate.to_le_bytes());
+        let byte_rate = self.sample_rate * u32::from(self.block_align());
+        fmt[8..12].copy_from_slice(&byte_rate.to_le_bytes());
+        fmt[12..14].copy_from_slice(&self.block_align().to_le_bytes());
+        fmt[14..16].copy_from_slice(&self.bits_per_sample.to_le_bytes());
+        fmt
+    }
+}
+
+/// A parsed WAV file, borrowing from its buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct Wav<'a> {
+    pub format: WavFormat,
+    /// The whole `fmt ` chunk body, including any extension fields.
+    fmt: &'a [u8],
+    pub data: &'a [u8],
+}
+
+/// Finds the `fmt ` and `data` chunks of a RIFF/WAVE buffer. A `data` size of
+/// `0xFFFFFFFF` or one running past the end (as written by streaming encoders)
+/// means "until the end of the buffer".
+pub fn parse_wav(buf: &[u8]) -> io::Result<Wav<'_>> {
+    if buf.len() < 12 || &buf[0..4] != b"RIFF" || &buf[8..12] != b"WAVE" {
+        return Err(invalid("not a RIFF/WAVE file"));
+    }
+
+    let mut fmt: Option<&[u8]> = None;
+    let mut pos = 12;
+    while pos + 8 <= buf.len() {
+        let id = &buf[pos..pos + 4];
+        let size = u32::from_le_bytes(buf[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let body = pos + 8;
+
+        if id == b"data" {
+            let fmt = fmt.ok_or_else(|| invalid("data chunk before fmt chunk"))?;
+            let end = if size == u32::MAX as usize || body + size > buf.len() {
+                buf.len()
+            } else {
+                body + size
+            };
+            let format = parse_fmt(fmt)?;
+            // Drop a trailing partial sample frame, if any.
+            let align = usize::from(format.block_align().max(1));
+            let len = (end - body) / align * align;
+
+            return Ok(Wav {
+                format,
+                fmt,
+                data: &buf[body..body + len],
+            });
+        }
+
+        if body + size > buf.len() {
+            break;
+        }
+        if id == b"fmt " {
+            fmt = Some(&buf[body..body + size]);
+        }
+        // Chunks are padded to an even length.
+        pos = body + size + (size & 1);
+    }
+
+    Err(invalid("no data chunk found"))
+}
+
+fn parse_fmt(fmt: &[u8]) -> io::Result<WavFormat> {
+    if fmt.len() < 16 {
+        return Err(invalid("fmt chunk is too short"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([fmt[i], fmt[i + 1]]);
+
+    Ok(WavFormat {
+        audio_format: u16_at(0),
+        channels: u16_at(2),
+        sample_rate: u32::from_le_bytes(fmt[4..8].try_into().unwrap()),
+        bits_per_sample: u16_at(14),
+    })
+}
+
+/// Wraps raw samples in a canonical 44-byte header.
+pub fn wav_from_samples(format: &WavFormat, samples: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(44 + samples.len() + 1);
+    write_wav(&mut out, &format.fmt_chunk(), &[samples]);
+    out
+}
+
+/// Joins complete WAV buffers into one. Every input must have the same sample
+/// format, rate and channel count, otherwise an `InvalidData` error is returned.
+pub fn concat_wav<B: AsRef<[u8]>>(chunks: &[B]) -> io::Result<Vec<u8>> {
+    let mut parsed: Vec<Wav<'_>> = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        let wav = parse_wav(chunk.as_ref()).map_err(|e| invalid(&format!("chunk {i}: {e}")))?;
+        if let Some(first) = parsed.first() {
+            if first.format != wav.format {
+                return Err(invalid(&format!(
+                    "chunk {i}: {:?} doesn't match {:?} of the first chunk",
+                    wav.format, first.format
+                )));
+            }
+        }
+        parsed.push(wav);
+    }
+    let Some(first) = parsed.first() else {
+        return Err(invalid("nothing to join"));
+    };
+
+    let data: Vec<&[u8]> = parsed.iter().map(|w| w.data).collect();
+    let mut out = Vec::with_capacity(64 + data.iter().map(|d| d.len()).sum::<usize>());
+    write_wav(&mut out, first.fmt, &data);
+    Ok(out)
+}
+
+/// Sizes past 4 GiB are written as `0xFFFFFFFF`, which readers (including
+/// [`parse_wav`]) take as "until the end of the file".
+fn write_wav(out: &mut Vec<u8>, fmt: &[u8], data: &[&[u8]]) {
+    let data_len: usize = data.iter().map(|d| d.len()).sum();
+    let fmt_pad = fmt.len() & 1;
+    let data_pad = data_len & 1;
+    let riff_len = 4 + (8 + fmt.len() + fmt_pad) + (8 + data_len + data_pad);
+    let size = |len: usize| u32::try_from(len).unwrap_or(u32::MAX);
+
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&size(riff_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+    out.extend_from_slice(fmt);
+    out.resize(out.len() + fmt_pad, 0);
+
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&size(data_len).to_le_bytes());
+    for d in data {
+        out.extend_from_slice(d);
+    }
+    out.resize(out.len() + data_pad, 0);
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_data_chunks_behind_one_header() {
+        let format = WavFormat::pcm16(24000, 1);
+        let joined = concat_wav(&[
+            wav_from_samples(&format, &[1, 0, 2, 0]),
+            wav_from_samples(&format, &[3, 0]),
+        ])
+        .unwrap();
+
+        assert_eq!(joined.len(), 44 + 6);
+        assert_eq!(
+            u32::from_le_bytes(joined[4..8].try_into().unwrap()) as usize,
+            joined.len() - 8
+        );
+        let wav = parse_wav(&joined).unwrap();
+        assert_eq!(wav.format, format);
+        assert_eq!(wav.data, [1, 0, 2, 0, 3, 0]);
+    }
+
+    #[test]
+    fn pads_odd_length_data() {
+        let format = WavFormat {
+            audio_format: 1,
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 8,
+        };
+        let joined = concat_wav(&[
+            wav_from_samples(&format, &[0x80, 0x81]),
+            wav_from_samples(&format, &[0x82]),
+        ])
+        .unwrap();
+
+        assert_eq!(joined.len(), 44 + 4);
+        assert_eq!(parse_wav(&joined).unwrap().data, [0x80, 0x81, 0x82]);
+        assert_eq!(format.silence(Duration::from_millis(1)), vec![0x80; 8]);
+    }
+
+    #[test]
+    fn skips_other_chunks_and_reads_streamed_sizes() {
+        let format = WavFormat::pcm16(16000, 2);
+        let plain = wav_from_samples(&format, &[0; 8]);
+        let mut wav = plain[..36].to_vec();
+        wav.extend(b"LIST\x03\x00\x00\x00abc\x00");
+        wav.extend(b"data");
+        wav.extend(u32::MAX.to_le_bytes());
+        // Ten bytes: two whole sample frames and a partial one.
+        wav.extend([1; 10]);
+
+        let parsed = parse_wav(&wav).unwrap();
+        assert_eq!(parsed.format, format);
+        assert_eq!(parsed.data, [1; 8]);
+    }
+
+    #[test]
+    fn rejects_mismatched_or_invalid_input() {
+        let mismatched = concat_wav(&[
+            wav_from_samples(&WavFormat::pcm16(24000, 1), &[0, 0]),
+            wav_from_samples(&WavFormat::pcm16(22050, 1), &[0, 0]),
+        ]);
+        assert_eq!(mismatched.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(parse_wav(b"OggS").is_err());
+        assert!(concat_wav::<Vec<u8>>(&[]).is_err());
+    }
+}
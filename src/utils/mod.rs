@@ -1,2 +1,12 @@
+pub mod backoff;
 pub mod chunk_text_unicode;
+pub mod concat_flac;
 pub mod concat_mp3;
+pub mod concat_ogg;
+pub mod concat_wav;
+pub mod mp3_frame;
+pub mod mp3_info;
+pub mod natural_date;
+pub mod normalize_text;
+pub mod sigv4;
+pub mod speech_markup;
@@ -0,0 +1,465 @@
+//! Minimal MPEG audio frame parsing: just enough to walk the frames of an MP3,
+//! skip ID3 tags and recognize Xing/Info/VBRI header frames.
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegVersion {
+    V1,
+    V2,
+    V25,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    L1,
+    L2,
+    L3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMode {
+    Stereo,
+    JointStereo,
+    DualChannel,
+    Mono,
+}
+
+impl ChannelMode {
+    pub fn channels(self) -> u8 {
+        match self {
+            ChannelMode::Mono => 1,
+            _ => 2,
+        }
+    }
+}
+
+const BITRATES_V1_L1: [u32; 15] = [
+    0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+];
+const BITRATES_V1_L2: [u32; 15] = [
+    0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+];
+const BITRATES_V1_L3: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const BITRATES_V2_L1: [u32; 15] = [
+    0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+];
+const BITRATES_V2_L23: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// The fields of a 4-byte MPEG audio frame header we care about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version: MpegVersion,
+    pub layer: Layer,
+    /// `true` when a 16-bit CRC follows the header.
+    pub crc: bool,
+    pub bitrate_kbps: u32,
+    pub sample_rate: u32,
+    pub padding: bool,
+    pub channel_mode: ChannelMode,
+}
+
+impl FrameHeader {
+    /// Parses a frame header from the start of `bytes`.
+    ///
+    /// Returns `None` for anything that isn't a valid header, including
+    /// free-format and reserved bitrate/sample rate values.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let &[b0, b1, b2, b3, ..] = bytes else {
+            return None;
+        };
+        if b0 != 0xFF || b1 & 0xE0 != 0xE0 {
+            return None;
+        }
+
+        let version = match (b1 >> 3) & 0b11 {
+            0b00 => MpegVersion::V25,
+            0b10 => MpegVersion::V2,
+            0b11 => MpegVersion::V1,
+            _ => return None,
+        };
+        let layer = match (b1 >> 1) & 0b11 {
+            0b01 => Layer::L3,
+            0b10 => Layer::L2,
+            0b11 => Layer::L1,
+            _ => return None,
+        };
+
+        let bitrate_index = (b2 >> 4) as usize;
+        if bitrate_index == 0 || bitrate_index == 15 {
+            return None;
+        }
+        let sample_rate_index = ((b2 >> 2) & 0b11) as usize;
+        if sample_rate_index == 3 {
+            return None;
+        }
+
+        let channel_mode = match b3 >> 6 {
+            0b00 => ChannelMode::Stereo,
+            0b01 => ChannelMode::JointStereo,
+            0b10 => ChannelMode::DualChannel,
+            _ => ChannelMode::Mono,
+        };
+
+        Some(FrameHeader {
+            version,
+            layer,
+            crc: b1 & 0x01 == 0,
+            bitrate_kbps: bitrate_table(version, layer)[bitrate_index],
+            sample_rate: sample_rates(version)[sample_rate_index],
+            padding: (b2 >> 1) & 0x01 == 1,
+            channel_mode,
+        })
+    }
+
+    /// Encodes the header back into its 4-byte form.
+    ///
+    /// Panics if `bitrate_kbps` or `sample_rate` aren't valid for the version/layer.
+    pub fn to_bytes(self) -> [u8; 4] {
+        let version = match self.version {
+            MpegVersion::V25 => 0b00,
+            MpegVersion::V2 => 0b10,
+            MpegVersion::V1 => 0b11,
+        };
+        let layer = match self.layer {
+            Layer::L3 => 0b01,
+            Layer::L2 => 0b10,
+            Layer::L1 => 0b11,
+        };
+        let bitrate_index = bitrate_table(self.version, self.layer)
+            .iter()
+            .position(|&b| b == self.bitrate_kbps && b != 0)
+            .expect("bitrate is valid for this MPEG version and layer")
+            as u8;
+        let sample_rate_index = sample_rates(self.version)
+            .iter()
+            .position(|&r| r == self.sample_rate)
+            .expect("sample rate is valid for this MPEG version")
+            as u8;
+        let channel_mode = match self.channel_mode {
+            ChannelMode::Stereo => 0b00,
+            ChannelMode::JointStereo => 0b01,
+            ChannelMode::DualChannel => 0b10,
+            ChannelMode::Mono => 0b11,
+        };
+
+        [
+            0xFF,
+            0xE0 | (version << 3) | (layer << 1) | u8::from(!self.crc),
+            (bitrate_index << 4) | (sample_rate_index << 2) | (u8::from(self.padding) << 1),
+            channel_mode << 6,
+        ]
+    }
+
+    /// Bitrates (in kbps) this header's version and layer allow, lowest first.
+    pub fn bitrates(&self) -> &'static [u32] {
+        &bitrate_table(self.version, self.layer)[1..]
+    }
+
+    /// Total length of the frame in bytes, header included.
+    pub fn frame_len(&self) -> usize {
+        let bitrate = self.bitrate_kbps as usize * 1000;
+        let sample_rate = self.sample_rate as usize;
+        let padding = usize::from(self.padding);
+
+        match (self.layer, self.version) {
+            (Layer::L1, _) => (12 * bitrate / sample_rate + padding) * 4,
+            (Layer::L2, _) | (Layer::L3, MpegVersion::V1) => 144 * bitrate / sample_rate + padding,
+            (Layer::L3, _) => 72 * bitrate / sample_rate + padding,
+        }
+    }
+
+    /// Number of PCM samples (per channel) one frame decodes to.
+    pub fn samples_per_frame(&self) -> u32 {
+        match (self.layer, self.version) {
+            (Layer::L1, _) => 384,
+            (Layer::L2, _) | (Layer::L3, MpegVersion::V1) => 1152,
+            (Layer::L3, _) => 576,
+        }
+    }
+
+    /// Length of the Layer III side information that follows the header (and CRC).
+    pub fn side_info_len(&self) -> usize {
+        match (self.version, self.channel_mode) {
+            (MpegVersion::V1, ChannelMode::Mono) => 17,
+            (MpegVersion::V1, _) => 32,
+            (_, ChannelMode::Mono) => 9,
+            (_, _) => 17,
+        }
+    }
+
+    /// Offset from the start of the frame where a Xing/Info tag would live.
+    pub fn xing_offset(&self) -> usize {
+        4 + if self.crc { 2 } else { 0 } + self.side_info_len()
+    }
+}
+
+/// A frame of `header`'s size with everything after the header zeroed: no bits
+/// are allocated (Layer I/II) and there is no Huffman data (Layer III), so it
+/// decodes to silence. CRC and padding are switched off.
+pub fn silent_frame(header: &FrameHeader) -> Vec<u8> {
+    let header = FrameHeader {
+        crc: false,
+        padding: false,
+        ..*header
+    };
+    let mut frame = vec![0u8; header.frame_len()];
+    frame[..4].copy_from_slice(&header.to_bytes());
+    frame
+}
+
+/// Enough [`silent_frame`]s to last at least `duration`.
+pub fn silence(header: &FrameHeader, duration: Duration) -> Vec<u8> {
+    let frame_secs = f64::from(header.samples_per_frame()) / f64::from(header.sample_rate);
+    let count = (duration.as_secs_f64() / frame_secs).ceil() as usize;
+
+    silent_frame(header).repeat(count)
+}
+
+fn bitrate_table(version: MpegVersion, layer: Layer) -> &'static [u32; 15] {
+    match (version, layer) {
+        (MpegVersion::V1, Layer::L1) => &BITRATES_V1_L1,
+        (MpegVersion::V1, Layer::L2) => &BITRATES_V1_L2,
+        (MpegVersion::V1, Layer::L3) => &BITRATES_V1_L3,
+        (_, Layer::L1) => &BITRATES_V2_L1,
+        (_, _) => &BITRATES_V2_L23,
+    }
+}
+
+fn sample_rates(version: MpegVersion) -> [u32; 3] {
+    match version {
+        MpegVersion::V1 => [44100, 48000, 32000],
+        MpegVersion::V2 => [22050, 24000, 16000],
+        MpegVersion::V25 => [11025, 12000, 8000],
+    }
+}
+
+/// One MPEG audio frame borrowed from a larger buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    pub header: FrameHeader,
+    pub data: &'a [u8],
+}
+
+impl Frame<'_> {
+    /// Whether this is a Xing/Info (LAME) or VBRI header frame rather than audio.
+    pub fn is_vbr_header(&self) -> bool {
+        let xing = self.header.xing_offset();
+        let tag_at = |offset: usize| self.data.get(offset..offset + 4);
+
+        matches!(tag_at(xing), Some(b"Xing") | Some(b"Info")) || tag_at(36) == Some(b"VBRI")
+    }
+}
+
+/// Length of a leading ID3v2 tag (header, body and optional footer), or 0.
+pub fn id3v2_len(data: &[u8]) -> usize {
+    if data.len() < 10 || &data[..3] != b"ID3" {
+        return 0;
+    }
+    // Tag size is a 28-bit "synchsafe" integer: 7 bits per byte.
+    let size = data[6..10]
+        .iter()
+        .fold(0usize, |acc, &b| (acc << 7) | (b & 0x7F) as usize);
+    let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+
+    (10 + size + footer).min(data.len())
+}
+
+/// Strips a leading ID3v2 tag and a trailing ID3v1 tag, if present.
+pub fn strip_tags(data: &[u8]) -> &[u8] {
+    let data = &data[id3v2_len(data)..];
+
+    if data.len() >= 128 && &data[data.len() - 128..data.len() - 125] == b"TAG" {
+        &data[..data.len() - 128]
+    } else {
+        data
+    }
+}
+
+/// Walks every MPEG audio frame in `data` after stripping ID3 tags.
+///
+/// Bytes that don't line up with a valid frame are skipped. When hunting for
+/// sync (at the start, or after junk) a header only counts if another header,
+/// or the end of the buffer, follows it; a truncated trailing frame is dropped.
+pub fn frames(data: &[u8]) -> Vec<Frame<'_>> {
+    let data = strip_tags(data);
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    let mut in_sync = false;
+
+    while pos + 4 <= data.len() {
+        let header = FrameHeader::parse(&data[pos..]);
+        let end = header.map(|h| pos + h.frame_len()).unwrap_or(usize::MAX);
+
+        let accepted = match header {
+            Some(_) if end > data.len() => false,
+            Some(_) if in_sync => true,
+            Some(_) => data.len() - end < 4 || FrameHeader::parse(&data[end..]).is_some(),
+            None => false,
+        };
+
+        match header {
+            Some(header) if accepted => {
+                frames.push(Frame {
+                    header,
+                    data: &data[pos..end],
+                });
+                pos = end;
+                in_sync = true;
+            }
+            _ => {
+                pos += 1;
+                in_sync = false;
+            }
+        }
+    }
+
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(version: MpegVersion, bitrate_kbps: u32, sample_rate: u32) -> FrameHeader {
+        FrameHeader {
+            version,
+            layer: Layer::L3,
+            crc: false,
+            bitrate_kbps,
+            sample_rate,
+            padding: false,
+            channel_mode: ChannelMode::Mono,
+        }
+    }
+
+    #[test]
+    fn parses_mpeg1_layer3() {
+        let h = FrameHeader::parse(&[0xFF, 0xFB, 0x90, 0x64]).unwrap();
+        assert_eq!(h.version, MpegVersion::V1);
+        assert_eq!(h.layer, Layer::L3);
+        assert!(!h.crc);
+        assert_eq!(h.bitrate_kbps, 128);
+        assert_eq!(h.sample_rate, 44100);
+        assert!(!h.padding);
+        assert_eq!(h.channel_mode, ChannelMode::JointStereo);
+        assert_eq!(h.frame_len(), 417);
+        assert_eq!(h.samples_per_frame(), 1152);
+    }
+
+    #[test]
+    fn padding_adds_a_byte() {
+        let h = FrameHeader::parse(&[0xFF, 0xFB, 0x92, 0x64]).unwrap();
+        assert!(h.padding);
+        assert_eq!(h.frame_len(), 418);
+    }
+
+    #[test]
+    fn parses_mpeg2_and_mpeg25() {
+        let v2 = FrameHeader::parse(&[0xFF, 0xF3, 0x80, 0xC0]).unwrap();
+        assert_eq!(v2, header(MpegVersion::V2, 64, 22050));
+        assert_eq!(v2.frame_len(), 208);
+        assert_eq!(v2.samples_per_frame(), 576);
+
+        let v25 = FrameHeader::parse(&[0xFF, 0xE3, 0x48, 0xC0]).unwrap();
+        assert_eq!(v25, header(MpegVersion::V25, 32, 8000));
+        assert_eq!(v25.frame_len(), 288);
+        assert_eq!(v25.samples_per_frame(), 576);
+    }
+
+    #[test]
+    fn parses_layers_1_and_2() {
+        let l1 = FrameHeader::parse(&[0xFF, 0xFF, 0x10, 0xC0]).unwrap();
+        assert_eq!((l1.layer, l1.bitrate_kbps), (Layer::L1, 32));
+        assert_eq!(l1.frame_len(), 32);
+        let l2 = FrameHeader::parse(&[0xFF, 0xFD, 0x10, 0xC0]).unwrap();
+        assert_eq!((l2.layer, l2.bitrate_kbps), (Layer::L2, 32));
+        assert_eq!(l2.frame_len(), 104);
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        // Free format (bitrate index 0) and the reserved bitrate index 15.
+        assert_eq!(FrameHeader::parse(&[0xFF, 0xFB, 0x00, 0x64]), None);
+        assert_eq!(FrameHeader::parse(&[0xFF, 0xFB, 0xF0, 0x64]), None);
+        // Reserved sample rate, version and layer.
+        assert_eq!(FrameHeader::parse(&[0xFF, 0xFB, 0x9C, 0x64]), None);
+        assert_eq!(FrameHeader::parse(&[0xFF, 0xEB, 0x90, 0x64]), None);
+        assert_eq!(FrameHeader::parse(&[0xFF, 0xF9, 0x90, 0x64]), None);
+        // No sync, or too short.
+        assert_eq!(FrameHeader::parse(&[0xFE, 0xFB, 0x90, 0x64]), None);
+        assert_eq!(FrameHeader::parse(&[0xFF, 0xFB, 0x90]), None);
+    }
+
+    #[test]
+    fn headers_round_trip() {
+        // Mode extension, copyright and emphasis bits aren't kept.
+        for bytes in [
+            [0xFF, 0xFB, 0x92, 0x40],
+            [0xFF, 0xFA, 0x90, 0x00],
+            [0xFF, 0xF3, 0x80, 0xC0],
+            [0xFF, 0xE3, 0x48, 0xC0],
+        ] {
+            assert_eq!(FrameHeader::parse(&bytes).unwrap().to_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn strips_id3_tags() {
+        let audio = silent_frame(&header(MpegVersion::V1, 128, 44100));
+        // ID3v2 with a synchsafe size of 130 (0x01 0x02) and a footer.
+        let mut tagged = b"ID3\x04\x00\x10\x00\x00\x01\x02".to_vec();
+        tagged.extend([0u8; 140]);
+        tagged.extend(&audio);
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, b' ');
+        tagged.extend(&id3v1);
+
+        assert_eq!(id3v2_len(&tagged), 150);
+        assert_eq!(strip_tags(&tagged), &audio[..]);
+        assert_eq!(strip_tags(&audio), &audio[..]);
+    }
+
+    #[test]
+    fn walks_frames_past_junk() {
+        let frame = silent_frame(&header(MpegVersion::V1, 128, 44100));
+        let mut data = b"junk".to_vec();
+        data.extend(&frame);
+        data.extend(&frame);
+        data.extend(&frame[..100]);
+
+        let frames = frames(&data);
+        assert_eq!(frames.len(), 2);
+        assert!(frames.iter().all(|f| f.data == &frame[..]));
+    }
+
+    #[test]
+    fn recognizes_vbr_header_frames() {
+        let h = header(MpegVersion::V1, 128, 44100);
+        let mut info = silent_frame(&h);
+        info[h.xing_offset()..h.xing_offset() + 4].copy_from_slice(b"Info");
+        let audio = silent_frame(&h);
+
+        assert!(Frame {
+            header: h,
+            data: &info
+        }
+        .is_vbr_header());
+        assert!(!Frame {
+            header: h,
+            data: &audio
+        }
+        .is_vbr_header());
+    }
+
+    #[test]
+    fn silence_lasts_at_least_the_duration() {
+        let h = header(MpegVersion::V2, 32, 24000);
+        // 24 ms frames: 100 ms needs 5 of them.
+        let audio = silence(&h, Duration::from_millis(100));
+        assert_eq!(audio.len(), 5 * h.frame_len());
+        assert_eq!(frames(&audio).len(), 5);
+    }
+}
@@ -0,0 +1,320 @@
+//! MP3 inspection without decoding: duration, bitrate, stream parameters and
+//! a list of the byte ranges that aren't healthy audio frames.
+
+use std::io;
+use std::time::Duration;
+
+use crate::utils::mp3_frame::{self, strip_tags, Frame, FrameHeader, Layer, MpegVersion};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bitrate {
+    /// Every audio frame has this bitrate, in kbps.
+    Constant(u32),
+    /// Frame bitrates vary; `average_kbps` is the audio bytes over the duration.
+    Variable {
+        average_kbps: f64,
+        min_kbps: u32,
+        max_kbps: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptionKind {
+    /// Bytes between frames (or a truncated last frame) that don't sync.
+    Junk,
+    /// A Layer III frame whose CRC-16 doesn't match its side info.
+    CrcMismatch,
+    /// A frame whose sample rate, channel count or layer differs from the
+    /// first frame's; players tend to glitch or stop there.
+    FormatChange,
+}
+
+/// A damaged stretch of the buffer; `offset` counts from its first byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Corruption {
+    pub offset: usize,
+    pub len: usize,
+    pub kind: CorruptionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mp3Info {
+    pub version: MpegVersion,
+    pub layer: Layer,
+    pub sample_rate: u32,
+    pub channels: u8,
+    /// Audio frames, not counting a Xing/Info/VBRI header frame.
+    pub frames: u64,
+    pub duration: Duration,
+    pub bitrate: Bitrate,
+    /// Whether a Xing/Info/VBRI header frame leads the audio.
+    pub has_vbr_header: bool,
+    pub corrupt: Vec<Corruption>,
+}
+
+impl Mp3Info {
+    pub fn is_corrupt(&self) -> bool {
+        !self.corrupt.is_empty()
+    }
+}
+
+/// Parses `data` frame by frame. The stream parameters come from the first
+/// audio frame; frames that don't match it still count towards the duration
+/// but are reported in [`Mp3Info::corrupt`]. Returns an `InvalidData` error
+/// when there is no audio at all.
+pub fn inspect_mp3(data: &[u8]) -> io::Result<Mp3Info> {
+    let mut frames = mp3_frame::frames(data);
+    let mut corrupt = junk(data, &frames);
+    let has_vbr_header = frames.first().is_some_and(Frame::is_vbr_header);
+    if has_vbr_header {
+        frames.remove(0);
+    }
+    let Some(first) = frames.first().map(|f| f.header) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no MPEG audio frames found",
+        ));
+    };
+
+    let mut samples = 0u64;
+    let mut audio_bytes = 0u64;
+    for frame in &frames {
+        samples += u64::from(frame.header.samples_per_frame());
+        audio_bytes += frame.data.len() as u64;
+
+        let kind = if !same_format(&first, &frame.header) {
+            Some(CorruptionKind::FormatChange)
+        } else if !crc_ok(frame) {
+            Some(CorruptionKind::CrcMismatch)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            corrupt.push(Corruption {
+                offset: offset_in(data, frame.data),
+                len: frame.data.len(),
+                kind,
+            });
+        }
+    }
+    corrupt.sort_by_key(|c| c.offset);
+
+    let duration = Duration::from_secs_f64(samples as f64 / f64::from(first.sample_rate));
+    let min_kbps = frames.iter().map(|f| f.header.bitrate_kbps).min().unwrap();
+    let max_kbps = frames.iter().map(|f| f.header.bitrate_kbps).max().unwrap();
+    let bitrate = if min_kbps == max_kbps {
+        Bitrate::Constant(min_kbps)
+    } else {
+        Bitrate::Variable {
+            average_kbps: audio_bytes as f64 * 8.0 / 1000.0 / duration.as_secs_f64(),
+            min_kbps,
+            max_kbps,
+        }
+    };
+
+    Ok(Mp3Info {
+        version: first.version,
+        layer: first.layer,
+        sample_rate: first.sample_rate,
+        channels: first.channel_mode.channels(),
+        frames: frames.len() as u64,
+        duration,
+        bitrate,
+        has_vbr_header,
+        corrupt,
+    })
+}
+
+fn same_format(a: &FrameHeader, b: &FrameHeader) -> bool {
+    a.sample_rate == b.sample_rate
+        && a.channel_mode.channels() == b.channel_mode.channels()
+        && a.layer == b.layer
+}
+
+/// The bytes between `frames` (which all borrow from `data`), ignoring tags.
+fn junk(data: &[u8], frames: &[Frame<'_>]) -> Vec<Corruption> {
+    let audio = strip_tags(data);
+    let mut pos = offset_in(data, audio);
+    let end = pos + audio.len();
+
+    let mut gaps = Vec::new();
+    let mut push_gap = |from: usize, to: usize| {
+        if to > from {
+            gaps.push(Corruption {
+                offset: from,
+                len: to - from,
+                kind: CorruptionKind::Junk,
+            });
+        }
+    };
+    for frame in frames {
+        let offset = offset_in(data, frame.data);
+        push_gap(pos, offset);
+        pos = offset + frame.data.len();
+    }
+    push_gap(pos, end);
+    gaps
+}
+
+fn offset_in(data: &[u8], part: &[u8]) -> usize {
+    part.as_ptr() as usize - data.as_ptr() as usize
+}
+
+/// Checks the optional CRC-16 of a Layer III frame: it covers the last two
+/// header bytes and the side info. Other layers, and frames without a CRC,
+/// always pass.
+fn crc_ok(frame: &Frame<'_>) -> bool {
+    let header = &frame.header;
+    if !header.crc || header.layer != Layer::L3 {
+        return true;
+    }
+    let side_info_end = 6 + header.side_info_len();
+    let Some(side_info) = frame.data.get(6..side_info_end) else {
+        return false;
+    };
+
+    let crc = crc16(crc16(0xFFFF, &frame.data[2..4]), side_info);
+    crc == u16::from_be_bytes([frame.data[4], frame.data[5]])
+}
+
+/// CRC-16, polynomial 0x8005, as MPEG audio uses it.
+fn crc16(crc: u16, data: &[u8]) -> u16 {
+    data.iter().fold(crc, |crc, &byte| {
+        let mut crc = crc ^ (u16::from(byte) << 8);
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::concat_mp3::concat_mp3_bytes;
+    use crate::utils::mp3_frame::{silent_frame, ChannelMode};
+
+    fn header(bitrate_kbps: u32, sample_rate: u32) -> FrameHeader {
+        FrameHeader {
+            version: MpegVersion::V1,
+            layer: Layer::L3,
+            crc: false,
+            bitrate_kbps,
+            sample_rate,
+            padding: false,
+            channel_mode: ChannelMode::Mono,
+        }
+    }
+
+    fn frames(bitrate_kbps: u32, count: usize) -> Vec<u8> {
+        silent_frame(&header(bitrate_kbps, 44100)).repeat(count)
+    }
+
+    /// A frame with a CRC after the header; `valid` picks whether it matches.
+    fn crc_frame(valid: bool) -> Vec<u8> {
+        let header = FrameHeader {
+            crc: true,
+            ..header(128, 44100)
+        };
+        let mut frame = vec![0u8; header.frame_len()];
+        frame[..4].copy_from_slice(&header.to_bytes());
+        let side_info = &frame[6..6 + header.side_info_len()];
+        let crc = crc16(crc16(0xFFFF, &frame[2..4]), side_info) ^ u16::from(!valid);
+        frame[4..6].copy_from_slice(&crc.to_be_bytes());
+        frame
+    }
+
+    #[test]
+    fn measures_constant_bitrate_audio() {
+        let mut data = frames(128, 4);
+        data.extend(frames(128, 6));
+        let info = inspect_mp3(&data).unwrap();
+
+        assert_eq!(info.version, MpegVersion::V1);
+        assert_eq!(info.layer, Layer::L3);
+        assert_eq!((info.sample_rate, info.channels), (44100, 1));
+        assert_eq!(info.frames, 10);
+        assert_eq!(
+            info.duration,
+            Duration::from_secs_f64(10.0 * 1152.0 / 44100.0)
+        );
+        assert_eq!(info.bitrate, Bitrate::Constant(128));
+        assert!(!info.has_vbr_header);
+        assert!(!info.is_corrupt());
+    }
+
+    #[test]
+    fn measures_variable_bitrate_audio() {
+        let data = concat_mp3_bytes(&[frames(128, 2), frames(64, 2)]).unwrap();
+        let info = inspect_mp3(&data).unwrap();
+
+        assert!(info.has_vbr_header);
+        assert_eq!(info.frames, 4);
+        let Bitrate::Variable {
+            average_kbps,
+            min_kbps,
+            max_kbps,
+        } = info.bitrate
+        else {
+            panic!("expected a variable bitrate, got {:?}", info.bitrate);
+        };
+        assert_eq!((min_kbps, max_kbps), (64, 128));
+        assert!((average_kbps - 96.0).abs() < 1.0, "{average_kbps}");
+        assert!(!info.is_corrupt());
+    }
+
+    #[test]
+    fn reports_junk_and_truncated_frames() {
+        let frame_len = header(128, 44100).frame_len();
+        let mut data = frames(128, 2);
+        data.extend(b"garbage");
+        data.extend(frames(128, 2));
+        data.extend(&frames(128, 1)[..100]);
+        let info = inspect_mp3(&data).unwrap();
+
+        assert_eq!(info.frames, 4);
+        assert_eq!(
+            info.corrupt,
+            [
+                Corruption {
+                    offset: 2 * frame_len,
+                    len: 7,
+                    kind: CorruptionKind::Junk,
+                },
+                Corruption {
+                    offset: 4 * frame_len + 7,
+                    len: 100,
+                    kind: CorruptionKind::Junk,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_format_changes_and_bad_crcs() {
+        let mut data = frames(128, 1);
+        data.extend(silent_frame(&header(128, 48000)));
+        data.extend(crc_frame(true));
+        data.extend(crc_frame(false));
+        let info = inspect_mp3(&data).unwrap();
+
+        let kinds: Vec<_> = info.corrupt.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            [CorruptionKind::FormatChange, CorruptionKind::CrcMismatch]
+        );
+        assert_eq!(info.frames, 4);
+    }
+
+    #[test]
+    fn rejects_buffers_without_audio() {
+        for data in [&b""[..], b"not an mp3 at all", &frames(128, 1)[..100]] {
+            let e = inspect_mp3(data).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
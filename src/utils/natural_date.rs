@@ -0,0 +1,573 @@
+// src/utils/natural_date.rs
+//! Reads the dates people type when setting a follow-up — "tomorrow 9am",
+//! "next Tuesday 3pm", "in 2 weeks", "Oct 20th at noon" — and resolves them
+//! in the user's timezone. Deterministic and offline; phrasing it doesn't
+//! know is an error, which callers may hand to
+//! [`crate::services::date_agent`] instead.
+//!
+//! A weekday on its own is the coming one (today included, if the time is
+//! still ahead); "next Tuesday" is the Tuesday of next week, weeks starting
+//! on Monday. A date without a time is at [`DEFAULT_TIME`]; a time without
+//! a date is the next time the clock shows it.
+
+use chrono::{
+    DateTime, Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+    Weekday,
+};
+use regex::Regex;
+use std::sync::LazyLock;
+use thiserror::Error;
+
+/// Time of day for dates given without one: 9 in the morning.
+pub const DEFAULT_TIME: NaiveTime = match NaiveTime::from_hms_opt(9, 0, 0) {
+    Some(time) => time,
+    None => unreachable!(),
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DateParseError {
+    #[error("No date given")]
+    Empty,
+    /// The word, or the whole input, that couldn't be read.
+    #[error("Don't know how to read {0:?}")]
+    Unrecognized(String),
+    /// Read fine but names no real date or time, such as "February 30".
+    #[error("{0:?} isn't a valid date")]
+    Invalid(String),
+}
+
+/// `3pm`, `3:30pm`, `3:30 p.m.`, `15:00`.
+static CLOCK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{1,2})(?::(\d{2}))?(am|pm|a\.m\.|p\.m\.)?$").unwrap());
+/// `2026-10-20`.
+static ISO_DATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+/// `20`, `20th`, `1st`.
+static DAY_OF_MONTH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{1,2})(?:st|nd|rd|th)?$").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Unit {
+    fn parse(word: &str) -> Option<Self> {
+        let word = word.strip_suffix('s').unwrap_or(word);
+        Some(match word {
+            "minute" | "min" => Unit::Minute,
+            "hour" | "hr" => Unit::Hour,
+            "day" => Unit::Day,
+            "week" | "wk" => Unit::Week,
+            "month" => Unit::Month,
+            "year" | "yr" => Unit::Year,
+            _ => return None,
+        })
+    }
+}
+
+/// What a date that came out in the past is moved forward by.
+#[derive(Debug, Clone, Copy)]
+enum Roll {
+    Day,
+    Week,
+    Year,
+}
+
+/// Everything read from the input so far.
+#[derive(Debug, Default)]
+struct Parsed {
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    shift: Option<(u32, Unit)>,
+    roll: Option<Roll>,
+}
+
+/// Resolves `input` against `now` in `tz`.
+pub fn parse<Tz: TimeZone>(
+    input: &str,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Result<DateTime<Utc>, DateParseError> {
+    let lowered = input.to_lowercase().replace(',', " ");
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(DateParseError::Empty);
+    }
+    let local_now = now.with_timezone(tz).naive_local();
+    let today = local_now.date();
+    let invalid = || DateParseError::Invalid(input.trim().to_string());
+
+    let mut parsed = Parsed::default();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let rest = &words[i + 1..];
+        let unrecognized = || DateParseError::Unrecognized(word.to_string());
+        let mut date = None;
+        let mut time = None;
+        let mut used = 1;
+
+        match word {
+            "at" | "on" | "the" => {}
+            "today" => date = Some(today),
+            "tomorrow" => date = today.succ_opt(),
+            "tonight" => {
+                date = Some(today);
+                time = NaiveTime::from_hms_opt(20, 0, 0);
+            }
+            "in" => {
+                let (n, unit) = amount(rest).ok_or_else(unrecognized)?;
+                set(&mut parsed.shift, (n, unit), word)?;
+                used = 3;
+            }
+            "next" => match rest.first() {
+                Some(&w) if weekday(w).is_some() => {
+                    let days = weekday(w).unwrap().num_days_from_monday() as i64
+                        - today.weekday().num_days_from_monday() as i64
+                        + 7;
+                    date = Some(today + TimeDelta::days(days));
+                    used = 2;
+                }
+                Some(&w)
+                    if matches!(Unit::parse(w), Some(Unit::Week | Unit::Month | Unit::Year)) =>
+                {
+                    set(&mut parsed.shift, (1, Unit::parse(w).unwrap()), word)?;
+                    used = 2;
+                }
+                _ => return Err(unrecognized()),
+            },
+            "this" => match rest.first() {
+                Some(&w) if weekday(w).is_some() => {
+                    date = Some(coming(today, weekday(w).unwrap()));
+                    parsed.roll = Some(Roll::Week);
+                    used = 2;
+                }
+                Some(&w) if part_of_day(w).is_some() => {
+                    date = Some(today);
+                    time = part_of_day(w);
+                    used = 2;
+                }
+                _ => return Err(unrecognized()),
+            },
+            w if weekday(w).is_some() => {
+                date = Some(coming(today, weekday(w).unwrap()));
+                parsed.roll = Some(Roll::Week);
+            }
+            w if part_of_day(w).is_some() => time = part_of_day(w),
+            "noon" | "midday" => time = NaiveTime::from_hms_opt(12, 0, 0),
+            "midnight" => time = Some(NaiveTime::MIN),
+            w if ISO_DATE.is_match(w) => {
+                date = Some(NaiveDate::parse_from_str(w, "%Y-%m-%d").map_err(|_| invalid())?);
+            }
+            w if month(w).is_some() => {
+                // "October 20", "Oct 20th 2027"
+                let day = rest.first().and_then(|w| day_of_month(w));
+                let day = day.ok_or_else(unrecognized)?;
+                let year = rest.get(1).and_then(|w| year(w));
+                date =
+                    Some(calendar_date(year, month(w).unwrap(), day, today).ok_or_else(invalid)?);
+                parsed.roll = year.is_none().then_some(Roll::Year);
+                used = 2 + year.is_some() as usize;
+            }
+            w if day_of_month(w).is_some() && month_after_day(rest).is_some() => {
+                // "20 October", "20th of October 2027"
+                let (m, skipped) = month_after_day(rest).unwrap();
+                let year = rest.get(skipped).and_then(|w| year(w));
+                date = Some(
+                    calendar_date(year, m, day_of_month(w).unwrap(), today).ok_or_else(invalid)?,
+                );
+                parsed.roll = year.is_none().then_some(Roll::Year);
+                used = 1 + skipped + year.is_some() as usize;
+            }
+            _ => {
+                if let Some((t, n)) = clock(&words[i..]) {
+                    time = Some(t.ok_or_else(invalid)?);
+                    used = n;
+                } else if let Some((n, unit, m)) = amount_from_now(&words[i..]) {
+                    set(&mut parsed.shift, (n, unit), word)?;
+                    used = m;
+                } else {
+                    return Err(unrecognized());
+                }
+            }
+        }
+
+        if let Some(date) = date {
+            set(&mut parsed.date, date, word)?;
+        }
+        if let Some(time) = time {
+            set(&mut parsed.time, time, word)?;
+        }
+        i += used;
+    }
+
+    resolve_parsed(parsed, now, local_now, tz).ok_or_else(|| {
+        if words.iter().all(|w| matches!(*w, "at" | "on" | "the")) {
+            DateParseError::Unrecognized(input.trim().to_string())
+        } else {
+            invalid()
+        }
+    })
+}
+
+/// Turns what was read into an instant; `None` when it isn't one.
+fn resolve_parsed<Tz: TimeZone>(
+    parsed: Parsed,
+    now: DateTime<Utc>,
+    local_now: NaiveDateTime,
+    tz: &Tz,
+) -> Option<DateTime<Utc>> {
+    if let Some((n, unit)) = parsed.shift {
+        let n64 = n as i64;
+        let shifted = match unit {
+            // Counted in real time, so a DST change doesn't stretch them.
+            Unit::Minute | Unit::Hour if parsed.date.is_some() || parsed.time.is_some() => {
+                return None
+            }
+            Unit::Minute => return now.checked_add_signed(TimeDelta::try_minutes(n64)?),
+            Unit::Hour => return now.checked_add_signed(TimeDelta::try_hours(n64)?),
+            _ if parsed.date.is_some() => return None,
+            Unit::Day => local_now.checked_add_signed(TimeDelta::try_days(n64)?)?,
+            Unit::Week => local_now.checked_add_signed(TimeDelta::try_weeks(n64)?)?,
+            Unit::Month => local_now.checked_add_months(Months::new(n))?,
+            Unit::Year => local_now.checked_add_months(Months::new(n.checked_mul(12)?))?,
+        };
+        let time = parsed.time.unwrap_or(shifted.time());
+        return resolve_local(tz, shifted.date().and_time(time));
+    }
+
+    if parsed.date.is_none() && parsed.time.is_none() {
+        return None;
+    }
+    let roll = parsed.roll.or(parsed.date.is_none().then_some(Roll::Day));
+    let mut date = parsed.date.unwrap_or(local_now.date());
+    let time = parsed.time.unwrap_or(DEFAULT_TIME);
+    let mut at = resolve_local(tz, date.and_time(time))?;
+    if at <= now {
+        date = match roll {
+            Some(Roll::Day) => date.succ_opt()?,
+            Some(Roll::Week) => date.checked_add_signed(TimeDelta::weeks(1))?,
+            Some(Roll::Year) => date.with_year(date.year() + 1)?,
+            None => return Some(at),
+        };
+        at = resolve_local(tz, date.and_time(time))?;
+    }
+    Some(at)
+}
+
+/// The instant a local wall-clock time names in `tz`: the earlier one when
+/// clocks go back, an hour later when it falls in the gap as they go forward.
+pub fn resolve_local<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&local.checked_add_signed(TimeDelta::hours(1))?)
+                .earliest()
+        })
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Sets `slot` unless an earlier word already did ("tomorrow friday").
+fn set<T>(slot: &mut Option<T>, value: T, word: &str) -> Result<(), DateParseError> {
+    if slot.is_some() {
+        return Err(DateParseError::Unrecognized(word.to_string()));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn number(word: &str) -> Option<u32> {
+    Some(match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        digits => digits.parse().ok()?,
+    })
+}
+
+/// `2 weeks`, `an hour`.
+fn amount(words: &[&str]) -> Option<(u32, Unit)> {
+    match words {
+        [n, unit, ..] => Some((number(n)?, Unit::parse(unit)?)),
+        _ => None,
+    }
+}
+
+/// `2 weeks from now`, `3 days later`, and how many words that took.
+fn amount_from_now(words: &[&str]) -> Option<(u32, Unit, usize)> {
+    let (n, unit) = amount(words)?;
+    match &words[2..] {
+        ["from", "now", ..] => Some((n, unit, 4)),
+        ["later", ..] => Some((n, unit, 3)),
+        _ => None,
+    }
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// The next `weekday` on or after `today`.
+fn coming(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + TimeDelta::days(days as i64)
+}
+
+fn part_of_day(word: &str) -> Option<NaiveTime> {
+    let hour = match word {
+        "morning" => 9,
+        "afternoon" => 15,
+        "evening" => 18,
+        "night" => 20,
+        _ => return None,
+    };
+    NaiveTime::from_hms_opt(hour, 0, 0)
+}
+
+fn month(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    let word = word.strip_suffix('.').unwrap_or(word);
+    if word == "sept" {
+        return Some(9);
+    }
+    MONTHS
+        .iter()
+        .position(|m| *m == word || (word.len() == 3 && m.starts_with(word)))
+        .map(|i| i as u32 + 1)
+}
+
+/// The month after a day of the month, skipping an "of", and how many words
+/// that took.
+fn month_after_day(words: &[&str]) -> Option<(u32, usize)> {
+    match words {
+        ["of", m, ..] => Some((month(m)?, 2)),
+        [m, ..] => Some((month(m)?, 1)),
+        [] => None,
+    }
+}
+
+fn day_of_month(word: &str) -> Option<u32> {
+    let day = DAY_OF_MONTH.captures(word)?[1].parse().ok()?;
+    (1..=31).contains(&day).then_some(day)
+}
+
+fn year(word: &str) -> Option<i32> {
+    (word.len() == 4).then(|| word.parse().ok()).flatten()
+}
+
+/// `month`/`day` of `year`, or of this year when none was given.
+fn calendar_date(year: Option<i32>, month: u32, day: u32, today: NaiveDate) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year.unwrap_or(today.year()), month, day)
+}
+
+/// A clock time at the start of `words`: `Some(None)` for one that looks
+/// like a time but isn't (`13pm`), with how many words it took. A bare
+/// number isn't a time; it needs a colon or am/pm.
+fn clock(words: &[&str]) -> Option<(Option<NaiveTime>, usize)> {
+    let caps = CLOCK.captures(words.first()?)?;
+    let (meridiem, used) = match caps.get(3) {
+        Some(m) => (Some(m.as_str()), 1),
+        None => match words.get(1) {
+            Some(&m @ ("am" | "pm" | "a.m." | "p.m.")) => (Some(m), 2),
+            _ => (None, 1),
+        },
+    };
+    let minute: u32 = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+    let hour: u32 = caps[1].parse().ok()?;
+    let hour = match meridiem {
+        None if caps.get(2).is_none() => return None,
+        None => Some(hour),
+        Some(_) if !(1..=12).contains(&hour) => None,
+        Some(m) if m.starts_with('a') => Some(hour % 12),
+        Some(_) => Some(hour % 12 + 12),
+    };
+    Some((
+        hour.and_then(|h| NaiveTime::from_hms_opt(h, minute, 0)),
+        used,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    /// Wednesday, October 14th 2026, 10:30 in UTC-4.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 14, 14, 30, 0).unwrap()
+    }
+
+    fn tz() -> FixedOffset {
+        FixedOffset::west_opt(4 * 3600).unwrap()
+    }
+
+    /// Parses `input` and shows it as local time, `2026-10-15 09:00`.
+    fn local(input: &str) -> String {
+        parse(input, now(), &tz())
+            .unwrap_or_else(|e| panic!("{input:?}: {e}"))
+            .with_timezone(&tz())
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    fn error(input: &str) -> DateParseError {
+        parse(input, now(), &tz()).unwrap_err()
+    }
+
+    #[test]
+    fn days_relative_to_today() {
+        assert_eq!(local("today 3pm"), "2026-10-14 15:00");
+        assert_eq!(local("tomorrow"), "2026-10-15 09:00");
+        assert_eq!(local("Tomorrow at 9:15 am"), "2026-10-15 09:15");
+        assert_eq!(local("tonight"), "2026-10-14 20:00");
+        assert_eq!(local("tomorrow evening"), "2026-10-15 18:00");
+        assert_eq!(local("this afternoon"), "2026-10-14 15:00");
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(local("friday"), "2026-10-16 09:00");
+        assert_eq!(local("on Fri at noon"), "2026-10-16 12:00");
+        assert_eq!(local("this thursday"), "2026-10-15 09:00");
+        // Today, while the time is still ahead, otherwise a week on.
+        assert_eq!(local("wednesday 5pm"), "2026-10-14 17:00");
+        assert_eq!(local("wednesday 8am"), "2026-10-21 08:00");
+        assert_eq!(local("next Tuesday 3pm"), "2026-10-20 15:00");
+        assert_eq!(local("next friday"), "2026-10-23 09:00");
+        assert_eq!(local("next monday"), "2026-10-19 09:00");
+    }
+
+    #[test]
+    fn durations_from_now() {
+        assert_eq!(local("in 2 weeks"), "2026-10-28 10:30");
+        assert_eq!(local("in an hour"), "2026-10-14 11:30");
+        assert_eq!(local("in 90 minutes"), "2026-10-14 12:00");
+        assert_eq!(local("in three days at 4:45pm"), "2026-10-17 16:45");
+        assert_eq!(local("in a month"), "2026-11-14 10:30");
+        assert_eq!(local("2 days from now"), "2026-10-16 10:30");
+        assert_eq!(local("5 days later"), "2026-10-19 10:30");
+        assert_eq!(local("next week"), "2026-10-21 10:30");
+        assert_eq!(local("next year"), "2027-10-14 10:30");
+    }
+
+    #[test]
+    fn calendar_dates() {
+        assert_eq!(local("October 20"), "2026-10-20 09:00");
+        assert_eq!(local("oct 20th at 2pm"), "2026-10-20 14:00");
+        assert_eq!(local("20 October"), "2026-10-20 09:00");
+        assert_eq!(local("the 3rd of November 14:00"), "2026-11-03 14:00");
+        assert_eq!(local("Jan 5, 2027"), "2027-01-05 09:00");
+        assert_eq!(local("2026-12-01 noon"), "2026-12-01 12:00");
+        assert_eq!(local("sept 1"), "2027-09-01 09:00");
+        // Already past this year, so next year's.
+        assert_eq!(local("March 3"), "2027-03-03 09:00");
+    }
+
+    #[test]
+    fn times_alone_mean_the_next_one() {
+        assert_eq!(local("3pm"), "2026-10-14 15:00");
+        assert_eq!(local("at 3 pm"), "2026-10-14 15:00");
+        assert_eq!(local("9am"), "2026-10-15 09:00");
+        assert_eq!(local("12am"), "2026-10-15 00:00");
+        assert_eq!(local("12:30 p.m."), "2026-10-14 12:30");
+        assert_eq!(local("midnight"), "2026-10-15 00:00");
+        assert_eq!(local("23:59"), "2026-10-14 23:59");
+    }
+
+    #[test]
+    fn resolves_in_the_given_timezone() {
+        let utc = parse("tomorrow 9am", now(), &Utc).unwrap();
+        assert_eq!(utc, Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap());
+        let brisbane = FixedOffset::east_opt(10 * 3600).unwrap();
+        // Already Thursday in Brisbane, so tomorrow is Friday.
+        let at = parse("tomorrow 9am", now(), &brisbane).unwrap();
+        assert_eq!(at, Utc.with_ymd_and_hms(2026, 10, 15, 23, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn unknown_phrasing_is_an_error() {
+        assert_eq!(error(""), DateParseError::Empty);
+        assert_eq!(error("  "), DateParseError::Empty);
+        assert_eq!(
+            error("whenever you like"),
+            DateParseError::Unrecognized("whenever".to_string())
+        );
+        assert_eq!(
+            error("the day after tomorrow"),
+            DateParseError::Unrecognized("day".to_string())
+        );
+        assert_eq!(
+            error("tomorrow friday"),
+            DateParseError::Unrecognized("friday".to_string())
+        );
+        assert_eq!(error("at"), DateParseError::Unrecognized("at".to_string()));
+        assert_eq!(error("3"), DateParseError::Unrecognized("3".to_string()));
+        assert_eq!(
+            error("next"),
+            DateParseError::Unrecognized("next".to_string())
+        );
+    }
+
+    #[test]
+    fn impossible_dates_are_invalid() {
+        assert!(matches!(error("February 30"), DateParseError::Invalid(_)));
+        assert!(matches!(error("2026-13-01"), DateParseError::Invalid(_)));
+        assert!(matches!(error("13pm"), DateParseError::Invalid(_)));
+        assert!(matches!(error("25:00"), DateParseError::Invalid(_)));
+        assert!(matches!(
+            error("in 2 hours at 3pm"),
+            DateParseError::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn out_of_range_shifts_are_invalid() {
+        for input in [
+            "in 999999999 days",
+            "in 999999999 weeks",
+            "in 4000000000 hours",
+            "in 999999999 years",
+        ] {
+            assert!(
+                matches!(error(input), DateParseError::Invalid(_)),
+                "{input}"
+            );
+        }
+    }
+}
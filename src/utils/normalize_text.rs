@@ -0,0 +1,810 @@
+// src/utils/normalize_text.rs
+//! Rewrites the parts of CRM text that TTS engines read badly — emails, URLs,
+//! phone numbers, money, dates and abbreviations — into plain words. Runs
+//! before chunking, so sentence boundaries are judged on the spoken text.
+
+use chrono::NaiveDate;
+use regex::{Captures, Regex};
+use std::{str::FromStr, sync::LazyLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    /// Month-first dates, NANP phone numbers, "one hundred one".
+    #[default]
+    EnUs,
+    /// Day-first dates, UK phone numbers, "one hundred and one".
+    EnGb,
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "en" | "en-us" => Ok(Locale::EnUs),
+            "en-gb" => Ok(Locale::EnGb),
+            other => Err(format!("Unsupported locale: {other}")),
+        }
+    }
+}
+
+/// One kind of rewrite. They always run in the order of [`Rule::ALL`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    Emails,
+    Urls,
+    Dates,
+    Phones,
+    Currency,
+    Abbreviations,
+}
+
+impl Rule {
+    /// Emails before URLs so `www.` inside an address isn't taken for a link;
+    /// dates before phones so `2025-03-14` isn't read digit by digit.
+    pub const ALL: [Rule; 6] = [
+        Rule::Emails,
+        Rule::Urls,
+        Rule::Dates,
+        Rule::Phones,
+        Rule::Currency,
+        Rule::Abbreviations,
+    ];
+}
+
+/// Abbreviations both locales expand; the `bool` marks titles that come
+/// before a name, whose period never ends a sentence.
+const ABBREVIATIONS: &[(&str, &str, bool)] = &[
+    ("VP", "vice president", false),
+    ("SVP", "senior vice president", false),
+    ("EVP", "executive vice president", false),
+    ("Inc.", "Incorporated", false),
+    ("Corp.", "Corporation", false),
+    ("Co.", "Company", false),
+    ("Ltd.", "Limited", false),
+    ("Dept.", "department", false),
+    ("Mgr.", "manager", false),
+    ("approx.", "approximately", false),
+    ("e.g.", "for example", false),
+    ("i.e.", "that is", false),
+    ("etc.", "et cetera", false),
+    ("vs.", "versus", false),
+    ("Jr.", "Junior", false),
+    ("Sr.", "Senior", false),
+    ("Dr.", "Doctor", true),
+    ("Mr.", "Mister", true),
+    ("Mrs.", "Missus", true),
+    ("Ms.", "Miz", true),
+    ("Prof.", "Professor", true),
+];
+
+/// British style drops the period after contractions and writes company forms bare.
+const ABBREVIATIONS_EN_GB: &[(&str, &str, bool)] = &[
+    ("Ltd", "Limited", false),
+    ("plc", "P L C", false),
+    ("PLC", "P L C", false),
+    ("Dr", "Doctor", true),
+    ("Mr", "Mister", true),
+    ("Mrs", "Missus", true),
+    ("Ms", "Miz", true),
+];
+
+#[derive(Debug, Clone)]
+struct Abbreviation {
+    short: String,
+    long: String,
+    title: bool,
+}
+
+/// Locale-aware text normalizer.
+///
+/// # Example
+/// ```ignore
+/// let n = Normalizer::new(Locale::EnUs).with_abbreviation("ARR", "annual recurring revenue");
+/// assert_eq!(n.normalize("ARR is $2M."), "annual recurring revenue is two million dollars.");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    locale: Locale,
+    rules: Vec<Rule>,
+    abbreviations: Vec<Abbreviation>,
+    abbreviation_re: Regex,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new(Locale::default())
+    }
+}
+
+impl Normalizer {
+    /// Every rule, with the locale's built-in abbreviations.
+    pub fn new(locale: Locale) -> Self {
+        let mut table = ABBREVIATIONS.to_vec();
+        if locale == Locale::EnGb {
+            table.extend_from_slice(ABBREVIATIONS_EN_GB);
+        }
+        let abbreviations: Vec<Abbreviation> = table
+            .into_iter()
+            .map(|(short, long, title)| Abbreviation {
+                short: short.to_string(),
+                long: long.to_string(),
+                title,
+            })
+            .collect();
+
+        Self {
+            locale,
+            rules: Rule::ALL.to_vec(),
+            abbreviation_re: abbreviation_regex(&abbreviations),
+            abbreviations,
+        }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// Keeps only `rules`.
+    pub fn with_rules(mut self, rules: &[Rule]) -> Self {
+        self.rules = rules.to_vec();
+        self
+    }
+
+    pub fn without_rule(mut self, rule: Rule) -> Self {
+        self.rules.retain(|r| *r != rule);
+        self
+    }
+
+    /// Adds or replaces an abbreviation. Matching is case-sensitive and on
+    /// whole words.
+    pub fn with_abbreviation(mut self, short: &str, long: &str) -> Self {
+        self.abbreviations.retain(|a| a.short != short);
+        self.abbreviations.push(Abbreviation {
+            short: short.to_string(),
+            long: long.to_string(),
+            title: false,
+        });
+        self.abbreviation_re = abbreviation_regex(&self.abbreviations);
+        self
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in Rule::ALL.iter().filter(|r| self.rules.contains(r)) {
+            text = match rule {
+                Rule::Emails => expand_emails(&text),
+                Rule::Urls => expand_urls(&text),
+                Rule::Dates => expand_dates(&text, self.locale),
+                Rule::Phones => expand_phones(&text, self.locale),
+                Rule::Currency => expand_currency(&text, self.locale),
+                Rule::Abbreviations => self.expand_abbreviations(&text),
+            };
+        }
+        text
+    }
+
+    fn expand_abbreviations(&self, text: &str) -> String {
+        self.abbreviation_re
+            .replace_all(text, |caps: &Captures| {
+                let m = caps.get(0).unwrap();
+                let Some(abbr) = self.abbreviations.iter().find(|a| a.short == m.as_str()) else {
+                    return m.as_str().to_string();
+                };
+
+                // "Acme Inc. We ..." still ends a sentence after the expansion.
+                let rest = text[m.end()..].trim_start();
+                let ends_sentence = abbr.short.ends_with('.')
+                    && !abbr.title
+                    && rest.chars().next().is_none_or(char::is_uppercase);
+                if ends_sentence {
+                    format!("{}.", abbr.long)
+                } else {
+                    abbr.long.clone()
+                }
+            })
+            .into_owned()
+    }
+}
+
+fn abbreviation_regex(abbreviations: &[Abbreviation]) -> Regex {
+    let mut shorts: Vec<&str> = abbreviations.iter().map(|a| a.short.as_str()).collect();
+    // Longest first, so "Mrs." wins over "Mr".
+    shorts.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let alternatives: Vec<String> = shorts
+        .iter()
+        .map(|s| {
+            let escaped = regex::escape(s);
+            if s.ends_with(|c: char| c.is_alphanumeric()) {
+                format!(r"{escaped}\b")
+            } else {
+                escaped
+            }
+        })
+        .collect();
+
+    Regex::new(&format!(r"\b(?:{})", alternatives.join("|"))).expect("valid abbreviation regex")
+}
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[\w.+-]+@[\w-]+(?:\.[\w-]+)+\b").unwrap());
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(?:https?://|www\.)[^\s<>"]+"#).unwrap());
+
+/// Reads `.`, `@`, `-`, `_`, `+` and `/` as words.
+fn spell_symbols(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 2);
+    for c in s.chars() {
+        let word = match c {
+            '.' => " dot ",
+            '@' => " at ",
+            '-' => " dash ",
+            '_' => " underscore ",
+            '+' => " plus ",
+            '/' => " slash ",
+            c => {
+                out.push(c);
+                continue;
+            }
+        };
+        out.push_str(word);
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn expand_emails(text: &str) -> String {
+    EMAIL
+        .replace_all(text, |caps: &Captures| spell_symbols(&caps[0]))
+        .into_owned()
+}
+
+/// `https://www.acme.com/pricing?ref=x` → "acme dot com slash pricing": the
+/// scheme, `www.`, query and fragment aren't worth reading out.
+fn expand_urls(text: &str) -> String {
+    URL.replace_all(text, |caps: &Captures| {
+        let url = &caps[0];
+        // Sentence punctuation right after a link isn't part of it.
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        let trailing = &url[trimmed.len()..];
+
+        let rest = trimmed
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_start_matches("www.");
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let rest = rest.trim_end_matches('/');
+
+        format!("{}{trailing}", spell_symbols(rest))
+    })
+    .into_owned()
+}
+
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap());
+static SLASH_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{1,2})/(\d{1,2})/(\d{4})\b").unwrap());
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// ISO dates everywhere; `a/b/yyyy` is month-first in the US and day-first in
+/// the UK. Impossible dates are left alone.
+fn expand_dates(text: &str, locale: Locale) -> String {
+    let iso = ISO_DATE.replace_all(text, |caps: &Captures| {
+        let parse = |i: usize| caps[i].parse::<u32>().unwrap_or(0);
+        speak_date(parse(1), parse(2), parse(3), locale).unwrap_or_else(|| caps[0].to_string())
+    });
+
+    SLASH_DATE
+        .replace_all(&iso, |caps: &Captures| {
+            let parse = |i: usize| caps[i].parse::<u32>().unwrap_or(0);
+            let (month, day) = match locale {
+                Locale::EnUs => (parse(1), parse(2)),
+                Locale::EnGb => (parse(2), parse(1)),
+            };
+            speak_date(parse(3), month, day, locale).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn speak_date(year: u32, month: u32, day: u32, locale: Locale) -> Option<String> {
+    NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, day)?;
+    let month = MONTHS[month as usize - 1];
+    let year = year_words(year, locale);
+    let day = ordinal_words(u64::from(day), locale);
+
+    Some(match locale {
+        Locale::EnUs => format!("{month} {day}, {year}"),
+        Locale::EnGb => format!("the {day} of {month} {year}"),
+    })
+}
+
+/// Years the way people say them: "nineteen oh five", "twenty twenty-five",
+/// but "two thousand five".
+fn year_words(year: u32, locale: Locale) -> String {
+    let (hi, lo) = (u64::from(year / 100), u64::from(year % 100));
+    match year {
+        2000..=2009 => cardinal_words(u64::from(year), locale),
+        1000..=9999 if lo == 0 => format!("{} hundred", cardinal_words(hi, locale)),
+        1000..=9999 if lo < 10 => format!(
+            "{} oh {}",
+            cardinal_words(hi, locale),
+            cardinal_words(lo, locale)
+        ),
+        1000..=9999 => format!(
+            "{} {}",
+            cardinal_words(hi, locale),
+            cardinal_words(lo, locale)
+        ),
+        _ => cardinal_words(u64::from(year), locale),
+    }
+}
+
+/// NANP numbers, which need separators so plain numbers don't match.
+static PHONE_US: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+1[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-])\d{3}[\s.-]\d{4}\b").unwrap()
+});
+/// UK numbers, national (`020 7946 0000`) or international (`+44 (0)20 ...`).
+static PHONE_GB: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+44\s?(?:\(0\)\s?)?|\b0)\d{2,4}[\s-]?\d{3,4}[\s-]?\d{3,4}\b").unwrap()
+});
+/// Any other number written with a country code.
+static PHONE_INTL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\+\d{1,3}(?:[\s.-]?\(?\d{2,4}\)?){2,5}\b").unwrap());
+
+const DIGITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Reads a phone number digit by digit, pausing between the written groups:
+/// "(555) 123-4567" → "five five five, one two three, four five six seven".
+fn speak_phone(number: &str) -> String {
+    let mut groups = Vec::new();
+    if number.starts_with('+') {
+        groups.push("plus".to_string());
+    }
+    for group in number.split(|c: char| !c.is_ascii_digit()) {
+        if group.is_empty() {
+            continue;
+        }
+        let digits: Vec<&str> = group
+            .bytes()
+            .map(|b| DIGITS[usize::from(b - b'0')])
+            .collect();
+        groups.push(digits.join(" "));
+    }
+
+    // "plus" belongs with the country code.
+    let mut out = String::new();
+    for (i, group) in groups.iter().enumerate() {
+        if i > 0 {
+            out.push_str(if groups[i - 1] == "plus" { " " } else { ", " });
+        }
+        out.push_str(group);
+    }
+    out
+}
+
+fn expand_phones(text: &str, locale: Locale) -> String {
+    let national = match locale {
+        Locale::EnUs => &PHONE_US,
+        Locale::EnGb => &PHONE_GB,
+    };
+    let text = national.replace_all(text, |caps: &Captures| speak_phone(&caps[0]));
+    PHONE_INTL
+        .replace_all(&text, |caps: &Captures| speak_phone(&caps[0]))
+        .into_owned()
+}
+
+static CURRENCY: LazyLock<Regex> = LazyLock::new(|| {
+    let amount = r"\d{1,3}(?:,\d{3})+|\d+";
+    Regex::new(&format!(
+        r"(?:(?P<symbol>[$£€])\s?(?P<amount>{amount})(?:\.(?P<fraction>\d{{1,2}}))?(?:\s?(?P<scale>k|K|m|M|bn|BN)\b)?)|(?:\b(?P<amount2>{amount})(?:\.(?P<fraction2>\d{{1,2}}))?\s?(?P<code>USD|EUR|GBP)\b)"
+    ))
+    .unwrap()
+});
+
+struct Currency {
+    major: (&'static str, &'static str),
+    minor: (&'static str, &'static str),
+}
+
+const DOLLAR: Currency = Currency {
+    major: ("dollar", "dollars"),
+    minor: ("cent", "cents"),
+};
+const POUND: Currency = Currency {
+    major: ("pound", "pounds"),
+    minor: ("penny", "pence"),
+};
+const EURO: Currency = Currency {
+    major: ("euro", "euros"),
+    minor: ("cent", "cents"),
+};
+
+/// "$1,234.50" → "one thousand two hundred thirty-four dollars and fifty
+/// cents"; "£2.5M" → "two point five million pounds".
+fn expand_currency(text: &str, locale: Locale) -> String {
+    CURRENCY
+        .replace_all(text, |caps: &Captures| {
+            let currency = match caps
+                .name("symbol")
+                .or_else(|| caps.name("code"))
+                .map_or("", |m| m.as_str())
+            {
+                "£" | "GBP" => &POUND,
+                "€" | "EUR" => &EURO,
+                _ => &DOLLAR,
+            };
+            let amount = caps
+                .name("amount")
+                .or_else(|| caps.name("amount2"))
+                .map_or("", |m| m.as_str())
+                .replace(',', "");
+            let Ok(major) = amount.parse::<u64>() else {
+                return caps[0].to_string();
+            };
+            let fraction = caps
+                .name("fraction")
+                .or_else(|| caps.name("fraction2"))
+                .map(|m| m.as_str());
+
+            if let Some(scale) = caps.name("scale") {
+                let scale = match scale.as_str() {
+                    "k" | "K" => "thousand",
+                    "m" | "M" => "million",
+                    _ => "billion",
+                };
+                let number = match fraction {
+                    Some(f) => format!(
+                        "{} point {}",
+                        cardinal_words(major, locale),
+                        spell_digits(f)
+                    ),
+                    None => cardinal_words(major, locale),
+                };
+                return format!("{number} {scale} {}", currency.major.1);
+            }
+
+            // One fractional digit is tenths: "$0.5" is fifty cents.
+            let minor = fraction.map_or(0, |f| {
+                let value = f.parse::<u64>().unwrap_or(0);
+                if f.len() == 1 {
+                    value * 10
+                } else {
+                    value
+                }
+            });
+            let plural = |n: u64, (one, many): (&str, &str)| {
+                format!(
+                    "{} {}",
+                    cardinal_words(n, locale),
+                    if n == 1 { one } else { many }
+                )
+            };
+            match (major, minor) {
+                (_, 0) => plural(major, currency.major),
+                (0, _) => plural(minor, currency.minor),
+                _ => format!(
+                    "{} and {}",
+                    plural(major, currency.major),
+                    plural(minor, currency.minor)
+                ),
+            }
+        })
+        .into_owned()
+}
+
+fn spell_digits(digits: &str) -> String {
+    digits
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| DIGITS[usize::from(b - b'0')])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+fn below_hundred(n: u64) -> String {
+    match n {
+        0..=19 => ONES[n as usize].to_string(),
+        _ if n.is_multiple_of(10) => TENS[(n / 10) as usize].to_string(),
+        _ => format!("{}-{}", TENS[(n / 10) as usize], ONES[(n % 10) as usize]),
+    }
+}
+
+fn below_thousand(n: u64, locale: Locale, words: &mut Vec<String>) {
+    let (hundreds, rest) = (n / 100, n % 100);
+    if hundreds > 0 {
+        words.push(ONES[hundreds as usize].to_string());
+        words.push("hundred".to_string());
+        if rest > 0 && locale == Locale::EnGb {
+            words.push("and".to_string());
+        }
+    }
+    if rest > 0 {
+        words.push(below_hundred(rest));
+    }
+}
+
+/// Cardinal number in words; above a quadrillion the digits are read out.
+pub fn cardinal_words(n: u64, locale: Locale) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    if n >= 1_000_000_000_000_000 {
+        return spell_digits(&n.to_string());
+    }
+
+    let mut words = Vec::new();
+    let mut rest = n;
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            below_thousand(rest / scale, locale, &mut words);
+            words.push(name.to_string());
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        // British English: "one thousand and five".
+        if !words.is_empty() && rest < 100 && locale == Locale::EnGb {
+            words.push("and".to_string());
+        }
+        below_thousand(rest, locale, &mut words);
+    }
+    words.join(" ")
+}
+
+/// "twenty-first", "one hundred and second".
+pub fn ordinal_words(n: u64, locale: Locale) -> String {
+    let cardinal = cardinal_words(n, locale);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        w if w.ends_with('y') => format!("{}ieth", &w[..w.len() - 1]),
+        w => format!("{w}th"),
+    };
+    format!("{head}{last}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us(text: &str) -> String {
+        Normalizer::new(Locale::EnUs).normalize(text)
+    }
+
+    fn gb(text: &str) -> String {
+        Normalizer::new(Locale::EnGb).normalize(text)
+    }
+
+    #[test]
+    fn cardinals_follow_locale() {
+        assert_eq!(cardinal_words(0, Locale::EnUs), "zero");
+        assert_eq!(cardinal_words(21, Locale::EnUs), "twenty-one");
+        assert_eq!(
+            cardinal_words(1_234, Locale::EnUs),
+            "one thousand two hundred thirty-four"
+        );
+        assert_eq!(
+            cardinal_words(1_234, Locale::EnGb),
+            "one thousand two hundred and thirty-four"
+        );
+        assert_eq!(cardinal_words(1_005, Locale::EnGb), "one thousand and five");
+        assert_eq!(cardinal_words(2_000_000, Locale::EnUs), "two million");
+    }
+
+    #[test]
+    fn ordinals() {
+        assert_eq!(ordinal_words(1, Locale::EnUs), "first");
+        assert_eq!(ordinal_words(12, Locale::EnUs), "twelfth");
+        assert_eq!(ordinal_words(20, Locale::EnUs), "twentieth");
+        assert_eq!(ordinal_words(23, Locale::EnUs), "twenty-third");
+        assert_eq!(ordinal_words(31, Locale::EnUs), "thirty-first");
+    }
+
+    #[test]
+    fn emails() {
+        assert_eq!(
+            us("Write to jane.doe+crm@acme-corp.com today"),
+            "Write to jane dot doe plus crm at acme dash corp dot com today"
+        );
+    }
+
+    #[test]
+    fn urls() {
+        assert_eq!(
+            us("See https://www.acme.com/pricing?ref=mail."),
+            "See acme dot com slash pricing."
+        );
+        assert_eq!(us("Visit www.acme.io/"), "Visit acme dot io");
+    }
+
+    #[test]
+    fn iso_dates() {
+        assert_eq!(
+            us("Due 2025-03-14."),
+            "Due March fourteenth, twenty twenty-five."
+        );
+        assert_eq!(
+            gb("Due 2025-03-14."),
+            "Due the fourteenth of March twenty twenty-five."
+        );
+        assert_eq!(us("On 2005-01-02"), "On January second, two thousand five");
+        assert_eq!(us("Not 2025-13-40"), "Not 2025-13-40");
+    }
+
+    #[test]
+    fn impossible_dates_are_left_alone() {
+        assert_eq!(us("Not 2025-02-31"), "Not 2025-02-31");
+        assert_eq!(us("Not 2025-04-31"), "Not 2025-04-31");
+        assert_eq!(us("Not 02/29/2025"), "Not 02/29/2025");
+        assert_eq!(gb("Not 31/06/2025"), "Not 31/06/2025");
+        assert_eq!(
+            us("On 02/29/2024"),
+            "On February twenty-ninth, twenty twenty-four"
+        );
+    }
+
+    #[test]
+    fn slash_dates_follow_locale() {
+        assert_eq!(us("03/04/1999"), "March fourth, nineteen ninety-nine");
+        assert_eq!(gb("03/04/1999"), "the third of April nineteen ninety-nine");
+        assert_eq!(us("12/31/1905"), "December thirty-first, nineteen oh five");
+    }
+
+    #[test]
+    fn us_phones() {
+        assert_eq!(
+            us("Call (555) 123-4567."),
+            "Call five five five, one two three, four five six seven."
+        );
+        assert_eq!(
+            us("Call +1 555.123.4567"),
+            "Call plus one, five five five, one two three, four five six seven"
+        );
+        // Plain numbers aren't phone numbers.
+        assert_eq!(us("We sold 5551234567 units"), "We sold 5551234567 units");
+    }
+
+    #[test]
+    fn gb_phones() {
+        assert_eq!(
+            gb("Ring 020 7946 0000"),
+            "Ring zero two zero, seven nine four six, zero zero zero zero"
+        );
+        assert_eq!(
+            gb("Ring +44 20 7946 0000"),
+            "Ring plus four four, two zero, seven nine four six, zero zero zero zero"
+        );
+    }
+
+    #[test]
+    fn international_phones() {
+        assert_eq!(
+            us("Berlin office: +49 30 1234 5678"),
+            "Berlin office: plus four nine, three zero, one two three four, five six seven eight"
+        );
+    }
+
+    #[test]
+    fn currency() {
+        assert_eq!(
+            us("It costs $1,234.50 a seat"),
+            "It costs one thousand two hundred thirty-four dollars and fifty cents a seat"
+        );
+        assert_eq!(us("$1"), "one dollar");
+        assert_eq!(us("$0.99"), "ninety-nine cents");
+        assert_eq!(us("$0.5"), "fifty cents");
+        assert_eq!(gb("£3.01"), "three pounds and one penny");
+        assert_eq!(us("€20"), "twenty euros");
+        assert_eq!(us("100 USD"), "one hundred dollars");
+    }
+
+    #[test]
+    fn currency_scales() {
+        assert_eq!(us("a $5M deal"), "a five million dollars deal");
+        assert_eq!(gb("£2.5bn"), "two point five billion pounds");
+        assert_eq!(us("$40k ARR"), "forty thousand dollars ARR");
+    }
+
+    #[test]
+    fn abbreviations() {
+        assert_eq!(
+            us("Our VP met Dr. Smith at Acme Inc. yesterday"),
+            "Our vice president met Doctor Smith at Acme Incorporated yesterday"
+        );
+        // The expansion still ends the sentence.
+        assert_eq!(
+            us("She joined Acme Inc. Then she left."),
+            "She joined Acme Incorporated. Then she left."
+        );
+        assert_eq!(us("Acme Inc."), "Acme Incorporated.");
+        // Whole words only.
+        assert_eq!(us("VPN access"), "VPN access");
+    }
+
+    #[test]
+    fn gb_abbreviations() {
+        assert_eq!(gb("Mr Jones of Acme Ltd"), "Mister Jones of Acme Limited");
+        // Not a British abbreviation in the US table.
+        assert_eq!(us("Mr Jones"), "Mr Jones");
+    }
+
+    #[test]
+    fn custom_abbreviations_and_rules() {
+        let n = Normalizer::new(Locale::EnUs).with_abbreviation("ARR", "annual recurring revenue");
+        assert_eq!(
+            n.normalize("ARR is $2M."),
+            "annual recurring revenue is two million dollars."
+        );
+
+        let n = Normalizer::new(Locale::EnUs).without_rule(Rule::Currency);
+        assert_eq!(n.normalize("$5 for the VP"), "$5 for the vice president");
+
+        let n = Normalizer::new(Locale::EnUs).with_rules(&[Rule::Emails]);
+        assert_eq!(
+            n.normalize("a@b.io on 2025-01-01"),
+            "a at b dot io on 2025-01-01"
+        );
+    }
+
+    #[test]
+    fn locale_parsing() {
+        assert_eq!("en-US".parse(), Ok(Locale::EnUs));
+        assert_eq!("en_GB".parse(), Ok(Locale::EnGb));
+        assert!("fr-FR".parse::<Locale>().is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// src/utils/speech_markup.rs
+//! Lightweight markup for speech scripts. `[pause 500ms]`, `[pause 1.5s]` or a
+//! bare `[pause]` asks for silence, and a blank line between paragraphs gets a
+//! shorter one. `*emphasis*` markers are dropped so they aren't read out;
+//! everything else is plain text.
+
+use regex::Regex;
+use std::{sync::LazyLock, time::Duration};
+
+/// Length of a bare `[pause]`.
+pub const DEFAULT_PAUSE: Duration = Duration::from_millis(500);
+/// Silence between paragraphs.
+pub const PARAGRAPH_PAUSE: Duration = Duration::from_millis(750);
+/// Longer pauses are cut to this.
+pub const MAX_PAUSE: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Text(String),
+    Pause(Duration),
+}
+
+/// A pause tag, or a paragraph break (a line holding only whitespace).
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\[\s*pause(?:\s+(\d+(?:\.\d+)?)\s*(ms|s))?\s*\]|\n[ \t]*\n\s*").unwrap()
+});
+static EMPHASIS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\*([^*\s](?:[^*\n]*[^*\s])?)\*").unwrap());
+
+/// Splits `input` into text and pauses. Text segments are trimmed and never
+/// empty; adjacent pauses collapse into the longest one, and pauses after the
+/// last text are dropped.
+pub fn parse_markup(input: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut last = 0;
+
+    for caps in MARKUP.captures_iter(input) {
+        let tag = caps.get(0).unwrap();
+        push_text(&mut segments, &input[last..tag.start()]);
+
+        let pause = if !tag.as_str().starts_with('[') {
+            PARAGRAPH_PAUSE
+        } else if let (Some(value), Some(unit)) = (caps.get(1), caps.get(2)) {
+            let value: f64 = value.as_str().parse().unwrap_or(0.0);
+            let secs = if unit.as_str().eq_ignore_ascii_case("ms") {
+                value / 1000.0
+            } else {
+                value
+            };
+            Duration::from_secs_f64(secs.min(MAX_PAUSE.as_secs_f64()))
+        } else {
+            DEFAULT_PAUSE
+        };
+        push_pause(&mut segments, pause);
+        last = tag.end();
+    }
+    push_text(&mut segments, &input[last..]);
+
+    while let Some(Segment::Pause(_)) = segments.last() {
+        segments.pop();
+    }
+    segments
+}
+
+fn push_text(segments: &mut Vec<Segment>, text: &str) {
+    let text = EMPHASIS.replace_all(text, "$1");
+    let text = text.trim();
+    if !text.is_empty() {
+        segments.push(Segment::Text(text.to_string()));
+    }
+}
+
+fn push_pause(segments: &mut Vec<Segment>, pause: Duration) {
+    if pause.is_zero() {
+        return;
+    }
+    match segments.last_mut() {
+        Some(Segment::Pause(last)) => *last = (*last).max(pause),
+        _ => segments.push(Segment::Pause(pause)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Segment {
+        Segment::Text(s.to_string())
+    }
+
+    fn pause(ms: u64) -> Segment {
+        Segment::Pause(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn plain_text_is_one_segment() {
+        assert_eq!(parse_markup("  Hello there.  "), [text("Hello there.")]);
+        assert!(parse_markup("   ").is_empty());
+    }
+
+    #[test]
+    fn parses_pause_tags() {
+        assert_eq!(
+            parse_markup("One. [pause 250ms] Two. [PAUSE 1.5s] Three. [pause] Four."),
+            [
+                text("One."),
+                pause(250),
+                text("Two."),
+                pause(1500),
+                text("Three."),
+                pause(500),
+                text("Four."),
+            ]
+        );
+        assert_eq!(
+            parse_markup("Wait [ pause  2 S ] done"),
+            [text("Wait"), pause(2000), text("done")]
+        );
+    }
+
+    #[test]
+    fn long_pauses_are_capped() {
+        assert_eq!(
+            parse_markup("a [pause 90s] b"),
+            [text("a"), Segment::Pause(MAX_PAUSE), text("b")]
+        );
+    }
+
+    #[test]
+    fn paragraph_breaks_pause() {
+        assert_eq!(
+            parse_markup("First paragraph.\n \t\nSecond one.\nSame paragraph."),
+            [
+                text("First paragraph."),
+                Segment::Pause(PARAGRAPH_PAUSE),
+                text("Second one.\nSame paragraph."),
+            ]
+        );
+    }
+
+    #[test]
+    fn adjacent_pauses_collapse_and_trailing_ones_drop() {
+        assert_eq!(
+            parse_markup("a [pause 200ms]\n\n[pause 0ms][pause 100ms] b [pause 3s]\n\n"),
+            [text("a"), Segment::Pause(PARAGRAPH_PAUSE), text("b")]
+        );
+    }
+
+    #[test]
+    fn strips_emphasis_markers() {
+        assert_eq!(
+            parse_markup("This is *really* important, *very much so*."),
+            [text("This is really important, very much so.")]
+        );
+        // Not emphasis: spaced-out asterisks and unmatched ones.
+        assert_eq!(parse_markup("2 * 3 * 4"), [text("2 * 3 * 4")]);
+        assert_eq!(parse_markup("a *b"), [text("a *b")]);
+    }
+
+    #[test]
+    fn unknown_tags_are_text() {
+        assert_eq!(
+            parse_markup("[laugh] hi [pause 5min]"),
+            [text("[laugh] hi [pause 5min]")]
+        );
+    }
+}